@@ -0,0 +1,313 @@
+use crate::position::{Direction, Grid, Position};
+
+/// Set of positions of a grid, stored as one bit per cell
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GridBitSet {
+    bits: Vec<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl GridBitSet {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            bits: vec![0; (width * height).div_ceil(64)],
+            width,
+            height,
+        }
+    }
+
+    /// Create an empty set with the same dimensions as `grid`
+    pub fn for_grid<T: Copy>(grid: &Grid<T>) -> Self {
+        Self::new(grid.width(), grid.height())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        (pos.x < self.width && pos.y < self.height).then(|| pos.y * self.width + pos.x)
+    }
+
+    /// Add a position to the set. Returns whether the position was newly
+    /// inserted (same as `HashSet::insert`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is outside the grid
+    pub fn insert(&mut self, pos: Position) -> bool {
+        let idx = self.index(pos).expect("position out of bound");
+        self.insert_idx(idx)
+    }
+
+    pub fn insert_idx(&mut self, idx: usize) -> bool {
+        let (word, mask) = (idx / 64, 1 << (idx % 64));
+        let inserted = self.bits[word] & mask == 0;
+        self.bits[word] |= mask;
+        inserted
+    }
+
+    /// Remove a position from the set. Returns whether the position was
+    /// present.
+    pub fn remove(&mut self, pos: Position) -> bool {
+        let Some(idx) = self.index(pos) else {
+            return false;
+        };
+        let (word, mask) = (idx / 64, 1 << (idx % 64));
+        let removed = self.bits[word] & mask != 0;
+        self.bits[word] &= !mask;
+        removed
+    }
+
+    /// Positions outside the grid are never part of the set
+    pub fn contains(&self, pos: Position) -> bool {
+        self.index(pos).is_some_and(|idx| self.contains_idx(idx))
+    }
+
+    pub fn contains_idx(&self, idx: usize) -> bool {
+        self.bits
+            .get(idx / 64)
+            .is_some_and(|word| word & (1 << (idx % 64)) != 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    /// Number of positions in the set
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// Iterate over the positions, in index order (left to right, then top
+    /// to bottom)
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.bits
+            .iter()
+            .enumerate()
+            .flat_map(|(word_idx, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(word_idx * 64 + bit)
+                })
+            })
+            .map(|idx| Position::from_index(idx, self.width))
+    }
+}
+
+/// Set of directions, stored as a 4-bit mask
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirectionSet(u8);
+
+impl DirectionSet {
+    fn mask(dir: Direction) -> u8 {
+        1 << (dir as u8)
+    }
+
+    /// Returns whether the direction was newly inserted
+    pub fn insert(&mut self, dir: Direction) -> bool {
+        let inserted = !self.contains(dir);
+        self.0 |= Self::mask(dir);
+        inserted
+    }
+
+    /// Returns whether the direction was present
+    pub fn remove(&mut self, dir: Direction) -> bool {
+        let removed = self.contains(dir);
+        self.0 &= !Self::mask(dir);
+        removed
+    }
+
+    pub fn contains(self, dir: Direction) -> bool {
+        self.0 & Self::mask(dir) != 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Direction> {
+        Direction::list()
+            .iter()
+            .copied()
+            .filter(move |dir| self.contains(*dir))
+    }
+}
+
+/// Set of (position, direction) pairs of a grid, stored as a [`DirectionSet`]
+/// per cell
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GridDirectionSet {
+    data: Vec<DirectionSet>,
+    width: usize,
+    height: usize,
+}
+
+impl GridDirectionSet {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            data: vec![DirectionSet::default(); width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Create an empty set with the same dimensions as `grid`
+    pub fn for_grid<T: Copy>(grid: &Grid<T>) -> Self {
+        Self::new(grid.width(), grid.height())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        (pos.x < self.width && pos.y < self.height).then(|| pos.y * self.width + pos.x)
+    }
+
+    /// Returns whether the pair was newly inserted
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is outside the grid
+    pub fn insert(&mut self, pos: Position, dir: Direction) -> bool {
+        let idx = self.index(pos).expect("position out of bound");
+        self.data[idx].insert(dir)
+    }
+
+    /// Returns whether the pair was present
+    pub fn remove(&mut self, pos: Position, dir: Direction) -> bool {
+        self.index(pos)
+            .is_some_and(|idx| self.data[idx].remove(dir))
+    }
+
+    /// Positions outside the grid are never part of the set
+    pub fn contains(&self, pos: Position, dir: Direction) -> bool {
+        self.get(pos).contains(dir)
+    }
+
+    /// All the directions for a given position (empty if outside the grid)
+    pub fn get(&self, pos: Position) -> DirectionSet {
+        self.index(pos)
+            .map(|idx| self.data[idx])
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.fill(DirectionSet::default());
+    }
+
+    /// Number of (position, direction) pairs in the set
+    pub fn len(&self) -> usize {
+        self.data.iter().map(|set| set.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.iter().all(|set| set.is_empty())
+    }
+
+    /// Iterate over the pairs, in index order
+    pub fn iter(&self) -> impl Iterator<Item = (Position, Direction)> + '_ {
+        self.data.iter().enumerate().flat_map(|(idx, set)| {
+            let pos = Position::from_index(idx, self.width);
+            set.iter().map(move |dir| (pos, dir))
+        })
+    }
+
+    /// Iterate over the positions that have at least one direction
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, set)| !set.is_empty())
+            .map(|(idx, _)| Position::from_index(idx, self.width))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bitset() {
+        let mut set = GridBitSet::new(10, 7);
+        assert!(set.is_empty());
+        assert!(set.insert(Position::new(3, 2)));
+        assert!(!set.insert(Position::new(3, 2)));
+        assert!(set.insert(Position::new(9, 6)));
+        assert!(set.insert(Position::new(0, 0)));
+
+        assert!(set.contains(Position::new(3, 2)));
+        assert!(!set.contains(Position::new(2, 3)));
+        assert!(!set.contains(Position::new(10, 0)));
+        assert!(!set.contains(Position::new(usize::MAX, 0)));
+        assert_eq!(set.len(), 3);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                Position::new(0, 0),
+                Position::new(3, 2),
+                Position::new(9, 6)
+            ]
+        );
+
+        assert!(set.remove(Position::new(0, 0)));
+        assert!(!set.remove(Position::new(0, 0)));
+        assert_eq!(set.len(), 2);
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.iter().count(), 0);
+    }
+
+    #[test]
+    fn direction_set() {
+        let mut set = GridDirectionSet::new(4, 4);
+        assert!(set.insert(Position::new(1, 1), Direction::Up));
+        assert!(set.insert(Position::new(1, 1), Direction::Left));
+        assert!(!set.insert(Position::new(1, 1), Direction::Up));
+        assert!(set.insert(Position::new(3, 0), Direction::Down));
+
+        assert!(set.contains(Position::new(1, 1), Direction::Left));
+        assert!(!set.contains(Position::new(1, 1), Direction::Right));
+        assert!(!set.contains(Position::new(4, 1), Direction::Right));
+        assert_eq!(set.get(Position::new(1, 1)).len(), 2);
+        assert!(set.get(Position::new(0, 1)).is_empty());
+        assert_eq!(set.len(), 3);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                (Position::new(3, 0), Direction::Down),
+                (Position::new(1, 1), Direction::Up),
+                (Position::new(1, 1), Direction::Left),
+            ]
+        );
+        assert_eq!(set.positions().count(), 2);
+
+        set.clear();
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! Decimal digit manipulation on integers, without going through strings.
+
+/// Decimal digit operations on unsigned integers
+pub trait Digits: Sized + Copy {
+    /// `10^exp`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result doesn't fit in `Self`
+    fn pow10(exp: u32) -> Self;
+
+    /// Number of decimal digits (`0` has 1 digit)
+    fn digit_count(self) -> u32;
+
+    /// Split the number so that the right part has `n` digits, e.g.
+    /// `123456.split_at_digit(2) == (1234, 56)`. The right part may have
+    /// leading zeros (`1005.split_at_digit(2) == (10, 5)`).
+    fn split_at_digit(self, n: u32) -> (Self, Self);
+
+    /// Split a number with an even number of digits in two halves, e.g.
+    /// `1234.split_half() == Some((12, 34))`. Returns `None` for an odd number
+    /// of digits.
+    fn split_half(self) -> Option<(Self, Self)>;
+
+    /// Concatenate the digits of `self` and `other`, e.g.
+    /// `12.concat(345) == Some(12345)`. Returns `None` on overflow.
+    fn concat(self, other: Self) -> Option<Self>;
+
+    /// Remove the digits of `suffix` from the end of `self`, e.g.
+    /// `12345.strip_suffix(45) == Some(123)`.
+    ///
+    /// Same as `str::strip_suffix` on the string representations, except that
+    /// it returns `None` if nothing would be left (`45.strip_suffix(45)`).
+    fn strip_suffix(self, suffix: Self) -> Option<Self>;
+}
+
+macro_rules! impl_digits {
+    ($t:ty, $table:ident) => {
+        const $table: [$t; <$t>::MAX.ilog10() as usize + 1] = {
+            let mut table = [1; <$t>::MAX.ilog10() as usize + 1];
+            let mut i = 1;
+            while i < table.len() {
+                table[i] = table[i - 1] * 10;
+                i += 1;
+            }
+            table
+        };
+
+        impl Digits for $t {
+            #[inline]
+            fn pow10(exp: u32) -> Self {
+                $table[exp as usize]
+            }
+
+            #[inline]
+            fn digit_count(self) -> u32 {
+                self.checked_ilog10().map_or(1, |log| log + 1)
+            }
+
+            #[inline]
+            fn split_at_digit(self, n: u32) -> (Self, Self) {
+                match $table.get(n as usize) {
+                    Some(pow) => (self / pow, self % pow),
+                    None => (0, self),
+                }
+            }
+
+            #[inline]
+            fn split_half(self) -> Option<(Self, Self)> {
+                let count = self.digit_count();
+                (count % 2 == 0).then(|| self.split_at_digit(count / 2))
+            }
+
+            #[inline]
+            fn concat(self, other: Self) -> Option<Self> {
+                $table
+                    .get(other.digit_count() as usize)?
+                    .checked_mul(self)?
+                    .checked_add(other)
+            }
+
+            #[inline]
+            fn strip_suffix(self, suffix: Self) -> Option<Self> {
+                let count = suffix.digit_count();
+                if self.digit_count() <= count {
+                    return None;
+                }
+                let (left, right) = self.split_at_digit(count);
+                (right == suffix).then_some(left)
+            }
+        }
+    };
+}
+
+impl_digits!(u64, POW10_U64);
+impl_digits!(u128, POW10_U128);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn count() {
+        assert_eq!(0_u64.digit_count(), 1);
+        assert_eq!(9_u64.digit_count(), 1);
+        assert_eq!(10_u64.digit_count(), 2);
+        assert_eq!(u64::MAX.digit_count(), 20);
+        assert_eq!(u128::MAX.digit_count(), 39);
+        assert_eq!(u64::pow10(19), 10_000_000_000_000_000_000);
+        assert_eq!(u128::pow10(38), 10_u128.pow(38));
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(123456_u64.split_at_digit(2), (1234, 56));
+        assert_eq!(1005_u64.split_at_digit(2), (10, 5));
+        assert_eq!(12_u64.split_at_digit(5), (0, 12));
+        assert_eq!(u64::MAX.split_at_digit(20), (0, u64::MAX));
+        assert_eq!(1234_u64.split_half(), Some((12, 34)));
+        assert_eq!(1000_u64.split_half(), Some((10, 0)));
+        assert_eq!(123_u64.split_half(), None);
+        assert_eq!(0_u64.split_half(), None);
+    }
+
+    #[test]
+    fn concat_strip() {
+        assert_eq!(12_u64.concat(345), Some(12345));
+        assert_eq!(12_u64.concat(0), Some(120));
+        assert_eq!(0_u64.concat(7), Some(7));
+        assert_eq!(u64::MAX.concat(1), None);
+        assert_eq!(
+            1_u128.concat(u64::MAX as u128),
+            Some(10_u128.pow(20) + u64::MAX as u128)
+        );
+
+        assert_eq!(12345_u64.strip_suffix(45), Some(123));
+        assert_eq!(1034_u64.strip_suffix(34), Some(10));
+        assert_eq!(100_u64.strip_suffix(0), Some(10));
+        assert_eq!(45_u64.strip_suffix(45), None);
+        assert_eq!(12345_u64.strip_suffix(44), None);
+        assert_eq!(5_u64.strip_suffix(12345), None);
+    }
+}
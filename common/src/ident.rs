@@ -0,0 +1,160 @@
+//! Interning of short names (machines, wires, ...) into dense indices, so that
+//! graphs can use vectors instead of hashmaps.
+
+use std::{cmp::Ordering, fmt::Display};
+
+use rustc_hash::FxHashMap;
+
+/// Compact identifier for an interned name. Only meaningful together with the
+/// [`Interner`] that created it.
+///
+/// Ids are dense (`0..interner.len()`) so they can be used as vector indices.
+/// They are ordered by creation order, or by name after
+/// [`Interner::sort_by_name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ident(u32);
+
+impl Ident {
+    pub fn from_index(index: usize) -> Self {
+        Self(u32::try_from(index).expect("too many identifiers"))
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Bidirectional mapping between names and [`Ident`]s
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    names: Vec<Box<str>>,
+    ids: FxHashMap<Box<str>, Ident>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the id of `name`, creating a new one if needed
+    pub fn intern(&mut self, name: &str) -> Ident {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = Ident::from_index(self.names.len());
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        id
+    }
+
+    /// Get the id of an already interned name
+    pub fn get(&self, name: &str) -> Option<Ident> {
+        self.ids.get(name).copied()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `id` was not created by this interner
+    pub fn name(&self, id: Ident) -> &str {
+        &self.names[id.index()]
+    }
+
+    /// Helper to display an id as its name, e.g. `format!("{}", names.display(id))`
+    pub fn display(&self, id: Ident) -> Named<'_> {
+        Named { interner: self, id }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterate over the ids and their names, in id order
+    pub fn iter(&self) -> impl Iterator<Item = (Ident, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (Ident::from_index(idx), name.as_ref()))
+    }
+
+    /// Compare two ids by their names
+    pub fn cmp_names(&self, a: Ident, b: Ident) -> Ordering {
+        self.name(a).cmp(self.name(b))
+    }
+
+    /// Renumber the ids so that their order matches their names' order.
+    ///
+    /// Returns the mapping from the old ids to the new ones (indexed by the old
+    /// id), to update any id created before the call.
+    pub fn sort_by_name(&mut self) -> Vec<Ident> {
+        let mut order = (0..self.names.len()).collect::<Vec<_>>();
+        order.sort_unstable_by(|&a, &b| self.names[a].cmp(&self.names[b]));
+
+        let mut remap = vec![Ident(0); self.names.len()];
+        let mut names = Vec::with_capacity(self.names.len());
+        for (new_idx, old_idx) in order.into_iter().enumerate() {
+            let new_id = Ident::from_index(new_idx);
+            remap[old_idx] = new_id;
+            names.push(std::mem::take(&mut self.names[old_idx]));
+        }
+        self.names = names;
+        self.ids.values_mut().for_each(|id| *id = remap[id.index()]);
+
+        remap
+    }
+}
+
+/// An [`Ident`] displayed as its name. See [`Interner::display`].
+#[derive(Debug, Clone, Copy)]
+pub struct Named<'a> {
+    interner: &'a Interner,
+    id: Ident,
+}
+
+impl Display for Named<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.interner.name(self.id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut names = Interner::new();
+        let kh = names.intern("kh");
+        let tc = names.intern("tc");
+        let long = names.intern("a_much_longer_name");
+        assert_eq!(names.intern("kh"), kh);
+        assert_eq!(names.len(), 3);
+        assert_eq!(kh.index(), 0);
+        assert_eq!(long.index(), 2);
+
+        assert_eq!(names.get("tc"), Some(tc));
+        assert_eq!(names.get("zz"), None);
+        assert_eq!(names.name(long), "a_much_longer_name");
+        assert_eq!(
+            format!("{}-{}", names.display(kh), names.display(tc)),
+            "kh-tc"
+        );
+        assert_eq!(names.cmp_names(long, kh), Ordering::Less);
+    }
+
+    #[test]
+    fn sort() {
+        let mut names = Interner::new();
+        let ids = ["tc", "kh", "aq", "kh2"].map(|name| names.intern(name));
+
+        let remap = names.sort_by_name();
+        let ids = ids.map(|id| remap[id.index()]);
+        assert_eq!(ids.map(|id| id.index()), [3, 1, 0, 2]);
+        assert_eq!(ids.map(|id| names.name(id)), ["tc", "kh", "aq", "kh2"]);
+        assert_eq!(names.get("kh2"), Some(ids[3]));
+        assert!(ids[2] < ids[1] && ids[1] < ids[3] && ids[3] < ids[0]);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod bitset;
+pub mod digits;
 pub mod error;
+pub mod ident;
 pub mod input;
+pub mod math;
+pub mod memo;
 pub mod position;
+pub mod region;
@@ -0,0 +1,194 @@
+//! Integer number theory helpers.
+//!
+//! All the functions take `i64` and do their intermediate computations in
+//! `i128`, so that products of two values can't overflow. Results that may not
+//! fit back in an `i64` are returned as an `Option`.
+
+/// Greatest common divisor. The result is always non-negative, and
+/// `gcd(0, 0) == 0`.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // Only `gcd(i64::MIN, 0)` or `gcd(i64::MIN, i64::MIN)` can't be
+    // represented, and they are not worth making the API fallible
+    a as i64
+}
+
+/// Least common multiple, or `None` if it overflows. The result is always
+/// non-negative, and `lcm(0, n) == 0`.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let l = (a as i128 / gcd(a, b) as i128 * b as i128).abs();
+    i64::try_from(l).ok()
+}
+
+/// Extended Euclidean algorithm.
+///
+/// Returns `(g, x, y)` such that `a * x + b * y == g` with `g == gcd(a, b)`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = extended_gcd_i128(a as i128, b as i128);
+    // |x| <= |b/g| and |y| <= |a/g|, so they fit
+    (g as i64, x as i64, y as i64)
+}
+
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// Modular inverse of `a` modulo `m` (with `m > 0`), in `[0, m)`.
+///
+/// Returns `None` if `a` and `m` are not coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    assert!(m > 0, "modulus must be positive");
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Combine two congruences `x ≡ a1 (mod m1)` and `x ≡ a2 (mod m2)` into a
+/// single `x ≡ a (mod m)` with `m == lcm(m1, m2)`.
+///
+/// The moduli don't need to be coprime. Returns `None` if the congruences are
+/// incompatible or if the combined modulus overflows.
+pub fn crt_pair((a1, m1): (i64, i64), (a2, m2): (i64, i64)) -> Option<(i64, i64)> {
+    assert!(m1 > 0 && m2 > 0, "moduli must be positive");
+    let (a1, m1, a2, m2) = (a1 as i128, m1 as i128, a2 as i128, m2 as i128);
+
+    let (g, p, _) = extended_gcd_i128(m1, m2);
+    let diff = a2 - a1;
+    if diff % g != 0 {
+        return None;
+    }
+
+    // x = a1 + m1 * k, with m1 * k ≡ diff (mod m2)
+    //   => k ≡ (diff / g) * p (mod m2 / g)
+    let m2_g = m2 / g;
+    let k = ((diff / g) % m2_g * (p % m2_g)).rem_euclid(m2_g);
+    let m = m1 * m2_g;
+    let a = (a1 + m1 * k).rem_euclid(m);
+
+    Some((i64::try_from(a).ok()?, i64::try_from(m).ok()?))
+}
+
+/// Chinese Remainder Theorem: combine a list of `(residue, modulus)`
+/// congruences into a single one.
+///
+/// An empty list gives `(0, 1)` (i.e. any number). See [`crt_pair`] for the
+/// failure cases.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |acc, &congruence| crt_pair(acc, congruence))
+}
+
+/// Exact integer solution of the 2×2 linear system
+/// ```text
+///     a[0][0] * x + a[0][1] * y = b[0]
+///     a[1][0] * x + a[1][1] * y = b[1]
+/// ```
+/// using Cramer's rule.
+///
+/// Returns `None` if the system doesn't have a unique solution (zero
+/// determinant), if the solution is not integral, or if it doesn't fit in an
+/// `i64`.
+pub fn solve_2x2(a: [[i64; 2]; 2], b: [i64; 2]) -> Option<(i64, i64)> {
+    let [[a00, a01], [a10, a11]] = a.map(|row| row.map(|v| v as i128));
+    let [b0, b1] = b.map(|v| v as i128);
+
+    let det = a00 * a11 - a01 * a10;
+    if det == 0 {
+        return None;
+    }
+    let det_x = b0 * a11 - a01 * b1;
+    let det_y = a00 * b1 - b0 * a10;
+    if det_x % det != 0 || det_y % det != 0 {
+        return None;
+    }
+
+    Some((
+        i64::try_from(det_x / det).ok()?,
+        i64::try_from(det_y / det).ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(-4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(101, 103), Some(10403));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+    }
+
+    #[test]
+    fn extended() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 5), (0, 7), (7, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        // Large moduli, where the intermediate products overflow an i64
+        let (a, m) = crt_pair((-1, 1_000_000_007), (5, 998_244_353)).unwrap();
+        assert_eq!(m, 1_000_000_007 * 998_244_353);
+        assert_eq!(a.rem_euclid(1_000_000_007), 1_000_000_006);
+        assert_eq!(a.rem_euclid(998_244_353), 5);
+        assert_eq!(crt(&[(0, i64::MAX), (1, i64::MAX - 1)]), None);
+    }
+
+    #[test]
+    fn linear_system() {
+        // Day 13 sample, first machine
+        assert_eq!(
+            solve_2x2([[94, 22], [34, 67]], [8400, 5400]),
+            Some((80, 40))
+        );
+        // Second machine has no integral solution
+        assert_eq!(solve_2x2([[26, 67], [66, 21]], [12748, 12176]), None);
+        // Colinear
+        assert_eq!(solve_2x2([[1, 2], [2, 4]], [3, 6]), None);
+        // Large values
+        assert_eq!(
+            solve_2x2([[94, 22], [34, 67]], [10000000008400, 10000000005400]),
+            None
+        );
+        assert_eq!(
+            solve_2x2([[26, 67], [66, 21]], [10000000012748, 10000000012176]),
+            Some((118679050709, 103199174542))
+        );
+    }
+}
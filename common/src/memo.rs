@@ -0,0 +1,238 @@
+//! Memoization for recursive functions.
+//!
+//! Unlike a global cache (e.g. the `cached` crate's proc-macro), the caches are
+//! regular values owned by the caller, so they are dropped (or reused) as
+//! needed, don't need to be reset between runs, and keys can borrow from the
+//! input.
+
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use rustc_hash::FxHashMap;
+
+/// Cache statistics
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+/// Single-threaded memoization cache
+///
+/// ```
+/// # use common::memo::Memo;
+/// fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+///     if n < 2 {
+///         return n;
+///     }
+///     memo.get_or_insert_with(n, |memo| fibonacci(n - 1, memo) + fibonacci(n - 2, memo))
+/// }
+///
+/// let mut memo = Memo::new();
+/// assert_eq!(fibonacci(90, &mut memo), 2880067194370816120);
+/// assert_eq!(memo.stats().misses, 89);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: FxHashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: FxHashMap::default(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cache: FxHashMap::with_capacity_and_hasher(capacity, Default::default()),
+            ..Default::default()
+        }
+    }
+
+    /// Return the cached value for `key`, or compute it with `f` and cache it.
+    ///
+    /// `f` receives the cache so it can recurse.
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = f(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// Look up a value, counting it as a hit or a miss
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.cache.get(key).cloned();
+        if value.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        value
+    }
+
+    /// Insert a value directly, e.g. to seed the base cases
+    pub fn insert(&mut self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+
+    /// Remove all the entries and reset the statistics
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+/// Thread-safe memoization cache
+///
+/// The lock is not held while computing a missing value, so recursion works,
+/// but two threads may compute the same value concurrently (the result being
+/// the same, the second insertion is harmless).
+#[derive(Debug)]
+pub struct SyncMemo<K, V> {
+    cache: Mutex<FxHashMap<K, V>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<K, V> Default for SyncMemo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: Mutex::new(FxHashMap::default()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<K, V> SyncMemo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the cached value for `key`, or compute it with `f` and cache it.
+    ///
+    /// `f` receives the cache so it can recurse.
+    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce(&Self) -> V,
+    {
+        if let Some(value) = self.cache.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return value.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = f(self);
+        self.cache.lock().unwrap().insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.cache.lock().unwrap().len(),
+        }
+    }
+
+    /// Remove all the entries and reset the statistics
+    pub fn clear(&mut self) {
+        self.cache.get_mut().unwrap().clear();
+        *self.hits.get_mut() = 0;
+        *self.misses.get_mut() = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn count_paths(pos: (u8, u8), memo: &mut Memo<(u8, u8), u64>) -> u64 {
+        if pos.0 == 0 || pos.1 == 0 {
+            return 1;
+        }
+        memo.get_or_insert_with(pos, |memo| {
+            count_paths((pos.0 - 1, pos.1), memo) + count_paths((pos.0, pos.1 - 1), memo)
+        })
+    }
+
+    fn count_paths_sync(pos: (u8, u8), memo: &SyncMemo<(u8, u8), u64>) -> u64 {
+        if pos.0 == 0 || pos.1 == 0 {
+            return 1;
+        }
+        memo.get_or_insert_with(pos, |memo| {
+            count_paths_sync((pos.0 - 1, pos.1), memo) + count_paths_sync((pos.0, pos.1 - 1), memo)
+        })
+    }
+
+    #[test]
+    fn memo() {
+        let mut memo = Memo::new();
+        assert_eq!(count_paths((16, 16), &mut memo), 601080390);
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                hits: 15 * 15,
+                misses: 16 * 16,
+                entries: 16 * 16
+            }
+        );
+
+        assert_eq!(memo.get(&(16, 16)), Some(601080390));
+        assert_eq!(memo.get(&(17, 16)), None);
+        memo.clear();
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+
+    #[test]
+    fn sync_memo() {
+        let memo = SyncMemo::new();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| assert_eq!(count_paths_sync((16, 16), &memo), 601080390));
+            }
+        });
+        assert_eq!(memo.stats().entries, 16 * 16);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::position::{Grid, Position};
+
+/// Which neighbors are considered connected when grouping cells into regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Only cells sharing an edge (up, right, down, left)
+    Four,
+    /// Cells sharing an edge or a corner
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        const FOUR: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+        const EIGHT: [(isize, isize); 8] = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        match self {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        }
+    }
+}
+
+/// A connected component of a grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Index of the region in [`Regions::list`], also used in [`Regions::labels`]
+    pub label: usize,
+    /// Number of cells
+    pub area: usize,
+    /// Number of cell edges between the region and anything else (other
+    /// regions or the outside of the grid)
+    pub perimeter: usize,
+    /// Number of straight sides of the region's outline (including the
+    /// outline of any hole)
+    pub sides: usize,
+    /// Top-left corner of the bounding box (inclusive)
+    pub top_left: Position,
+    /// Bottom-right corner of the bounding box (inclusive)
+    pub bottom_right: Position,
+    /// Member cells, in visitation order
+    pub cells: Vec<Position>,
+}
+
+/// Result of [`Grid::regions`]
+pub struct Regions {
+    /// For each cell, the label of the region it belongs to
+    pub labels: Grid<usize>,
+    /// The regions, ordered by the index of their first cell
+    pub list: Vec<Region>,
+}
+
+impl<T> Grid<T>
+where
+    T: Copy,
+{
+    /// Group the cells into 4-connected regions. Two neighbors belong to the
+    /// same region if `eq` returns true for their values.
+    pub fn regions<F>(&self, eq: F) -> Regions
+    where
+        F: Fn(T, T) -> bool,
+    {
+        self.regions_with(Connectivity::Four, eq)
+    }
+
+    /// Same as [`Grid::regions`] but with a choice of connectivity.
+    ///
+    /// Perimeter and sides always describe the outline of the cells (i.e.
+    /// with 8-connectivity, two cells touching by a corner have a perimeter
+    /// of 8 and 8 sides).
+    pub fn regions_with<F>(&self, connectivity: Connectivity, eq: F) -> Regions
+    where
+        F: Fn(T, T) -> bool,
+    {
+        const UNLABELED: usize = usize::MAX;
+
+        let width = self.width();
+        let height = self.height();
+        let neighbor = |idx: usize, (dx, dy): (isize, isize)| {
+            let pos = Position::from_index(idx, width);
+            let x = pos.x.checked_add_signed(dx).filter(|x| *x < width)?;
+            let y = pos.y.checked_add_signed(dy).filter(|y| *y < height)?;
+            Some(y * width + x)
+        };
+
+        let mut labels = vec![UNLABELED; self.size()];
+        let mut list = Vec::new();
+        let mut to_check = Vec::new();
+
+        for start in 0..self.size() {
+            if labels[start] != UNLABELED {
+                continue;
+            }
+
+            let label = list.len();
+            labels[start] = label;
+            to_check.push(start);
+
+            let mut cells = Vec::new();
+            while let Some(idx) = to_check.pop() {
+                cells.push(idx);
+                let value = self.get_idx(idx).unwrap();
+                for &offset in connectivity.offsets() {
+                    if let Some(next) = neighbor(idx, offset) {
+                        if labels[next] == UNLABELED && eq(value, self.get_idx(next).unwrap()) {
+                            labels[next] = label;
+                            to_check.push(next);
+                        }
+                    }
+                }
+            }
+
+            // The region is fully labeled, we can now look at its outline
+            let in_region = |idx: usize, offset: (isize, isize)| {
+                neighbor(idx, offset).is_some_and(|next| labels[next] == label)
+            };
+            let mut perimeter = 0;
+            let mut sides = 0;
+            let mut top_left = Position::new(usize::MAX, usize::MAX);
+            let mut bottom_right = Position::new(0, 0);
+            for &idx in &cells {
+                let edges = Connectivity::Four.offsets();
+                perimeter += edges
+                    .iter()
+                    .filter(|&&offset| !in_region(idx, offset))
+                    .count();
+
+                // A region has as many sides as corners. For each pair of
+                // consecutive edges, we have an outer corner if both neighbors
+                // are outside, and an inner corner if both are inside but the
+                // diagonal isn't.
+                sides += edges
+                    .iter()
+                    .zip(edges.iter().cycle().skip(1))
+                    .filter(|(&a, &b)| {
+                        let diagonal = (a.0 + b.0, a.1 + b.1);
+                        match (in_region(idx, a), in_region(idx, b)) {
+                            (false, false) => true,
+                            (true, true) => !in_region(idx, diagonal),
+                            _ => false,
+                        }
+                    })
+                    .count();
+
+                let pos = Position::from_index(idx, width);
+                top_left = Position::new(top_left.x.min(pos.x), top_left.y.min(pos.y));
+                bottom_right = Position::new(bottom_right.x.max(pos.x), bottom_right.y.max(pos.y));
+            }
+
+            list.push(Region {
+                label,
+                area: cells.len(),
+                perimeter,
+                sides,
+                top_left,
+                bottom_right,
+                cells: cells
+                    .into_iter()
+                    .map(|idx| Position::from_index(idx, width))
+                    .collect(),
+            });
+        }
+
+        Regions {
+            labels: Grid::new(labels, width, height),
+            list,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid(input: &[u8]) -> Grid<u8> {
+        let lines: Vec<_> = input
+            .split(|b| *b == b'\n')
+            .filter(|l| !l.is_empty())
+            .collect();
+        let width = lines[0].len();
+        Grid::new(lines.concat(), width, lines.len())
+    }
+
+    #[test]
+    fn four_connected() {
+        let grid = grid(b"AAAA\nBBCD\nBBCC\nEEEC\n");
+        let regions = grid.regions(|a, b| a == b);
+
+        let summary: Vec<_> = regions
+            .list
+            .iter()
+            .map(|r| {
+                (
+                    grid.get_idx(r.cells[0].y * 4 + r.cells[0].x).unwrap(),
+                    r.area,
+                    r.perimeter,
+                    r.sides,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (b'A', 4, 10, 4),
+                (b'B', 4, 8, 4),
+                (b'C', 4, 10, 8),
+                (b'D', 1, 4, 4),
+                (b'E', 3, 8, 4),
+            ]
+        );
+
+        let c = &regions.list[2];
+        assert_eq!(c.top_left, Position::new(2, 1));
+        assert_eq!(c.bottom_right, Position::new(3, 3));
+        assert_eq!(regions.labels.get_idx(15), Some(2));
+    }
+
+    #[test]
+    fn hole() {
+        let grid = grid(b"AAA\nABA\nAAA\n");
+        let regions = grid.regions(|a, b| a == b);
+
+        assert_eq!(regions.list.len(), 2);
+        assert_eq!(regions.list[0].area, 8);
+        assert_eq!(regions.list[0].perimeter, 16);
+        assert_eq!(regions.list[0].sides, 8);
+        assert_eq!(regions.list[1].sides, 4);
+    }
+
+    #[test]
+    fn eight_connected() {
+        let grid = grid(b"AB\nBA\n");
+
+        let regions = grid.regions(|a, b| a == b);
+        assert_eq!(regions.list.len(), 4);
+
+        let regions = grid.regions_with(Connectivity::Eight, |a, b| a == b);
+        assert_eq!(regions.list.len(), 2);
+        assert_eq!(regions.list[0].area, 2);
+        assert_eq!(regions.list[0].perimeter, 8);
+        assert_eq!(regions.list[0].sides, 8);
+        assert_eq!(regions.list[0].top_left, Position::new(0, 0));
+        assert_eq!(regions.list[0].bottom_right, Position::new(1, 1));
+    }
+}
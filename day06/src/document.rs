@@ -3,6 +3,8 @@ use std::{
     ops::{Add, AddAssign},
 };
 
+pub use common::position::Direction;
+
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Position {
     pub x: usize,
@@ -84,21 +86,3 @@ impl AddAssign<Direction> for IPosition {
         *self = *self + rhs;
     }
 }
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-impl Direction {
-    pub fn right(self) -> Self {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-}
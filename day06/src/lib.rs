@@ -3,3 +3,4 @@ pub mod parse_fancy;
 pub mod parse_simple;
 pub mod part1;
 pub mod part2;
+pub mod patrol;
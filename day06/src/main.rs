@@ -1,6 +1,6 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
-use day06::{part1, part2};
+use day06::{part1, part2, patrol::Lab};
 use miette::Result;
 use std::path::PathBuf;
 
@@ -18,6 +18,15 @@ enum Command {
 
     /// Part 2
     Part2 { file: Option<PathBuf> },
+
+    /// List the obstruction positions making the guard loop
+    Patrol {
+        file: Option<PathBuf>,
+
+        /// Also print each cell of the route of the guard
+        #[arg(short, long)]
+        path: bool,
+    },
 }
 
 fn main() -> Result<()> {
@@ -30,6 +39,24 @@ fn main() -> Result<()> {
         Some(Command::Part2 { file }) => {
             println!("Result: {}", part2::run(&read_input_u8!(file)?)?)
         }
+        Some(Command::Patrol { file, path }) => {
+            let lab = Lab::new(&read_input_u8!(file)?)?;
+            let patrol = lab.patrol(None);
+            if path {
+                for (position, direction) in &patrol.path {
+                    println!("{position:?} {direction:?}");
+                }
+            }
+            println!("Visited: {}", patrol.visited().len());
+            if patrol.looping {
+                println!("The guard is already looping");
+            }
+            let obstructions = lab.obstructions();
+            for position in &obstructions {
+                println!("Obstruction: {position:?}");
+            }
+            println!("Result: {}", obstructions.len())
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
@@ -1,5 +1,6 @@
 use std::{collections::HashSet, ops::Range};
 
+use common::{bitset::GridDirectionSet, position::Position};
 use miette::Result;
 
 use crate::{
@@ -30,24 +31,31 @@ pub fn run(content: &[u8]) -> Result<u64> {
     Ok(check(&grid, (range_x, range_y), start, Direction::Up, None))
 }
 
+/// Positions are never negative (they come from the parser's line/column
+/// indices), so we can use them directly as grid positions
+fn to_grid_pos(position: IPosition) -> Position {
+    Position::new(position.x as usize, position.y as usize)
+}
+
 fn check(
     grid: &HashSet<IPosition>,
     range: (Range<isize>, Range<isize>),
     mut position: IPosition,
     mut direction: Direction,
-    supplemental: Option<(&HashSet<(IPosition, Direction)>, IPosition)>,
+    supplemental: Option<(&GridDirectionSet, IPosition)>,
 ) -> u64 {
-    let mut visited = HashSet::new();
+    let mut visited = GridDirectionSet::new(range.0.end as usize, range.1.end as usize);
     let mut count = 0;
     loop {
-        if visited.contains(&(position, direction))
-            || supplemental.is_some_and(|previsited| previsited.0.contains(&(position, direction)))
+        let grid_pos = to_grid_pos(position);
+        if visited.contains(grid_pos, direction)
+            || supplemental.is_some_and(|previsited| previsited.0.contains(grid_pos, direction))
         {
             // Found a loop
             //print_grid(grid, range, &visited, supplemental);
             return count + 1;
         }
-        visited.insert((position, direction));
+        visited.insert(grid_pos, direction);
 
         let next = position + direction;
 
@@ -65,12 +73,7 @@ fn check(
         // check if blocking the path would create loop
         // if we can block twice (i.e. supplemental must be None) and we can't
         // block a place we already visited
-        if supplemental.is_none()
-            && !visited.contains(&(next, Direction::Up))
-            && !visited.contains(&(next, Direction::Right))
-            && !visited.contains(&(next, Direction::Down))
-            && !visited.contains(&(next, Direction::Left))
-        {
+        if supplemental.is_none() && visited.get(to_grid_pos(next)).is_empty() {
             count += check(
                 grid,
                 range.clone(),
@@ -88,8 +91,8 @@ fn check(
 fn print_grid(
     grid: &HashSet<IPosition>,
     range: (Range<isize>, Range<isize>),
-    visited: &HashSet<(IPosition, Direction)>,
-    supplemental: Option<(&HashSet<(IPosition, Direction)>, IPosition)>,
+    visited: &GridDirectionSet,
+    supplemental: Option<(&GridDirectionSet, IPosition)>,
 ) {
     let (range_x, range_y) = range;
     println!("Grid:");
@@ -102,17 +105,17 @@ fn print_grid(
             } else if supplemental.is_some_and(|s| s.1 == position) {
                 print!("O");
             } else {
-                let h = visited.contains(&(position, Direction::Left))
-                    || visited.contains(&(position, Direction::Right))
+                let h = visited.contains(to_grid_pos(position), Direction::Left)
+                    || visited.contains(to_grid_pos(position), Direction::Right)
                     || supplemental.is_some_and(|s| {
-                        s.0.contains(&(position, Direction::Left))
-                            || s.0.contains(&(position, Direction::Right))
+                        s.0.contains(to_grid_pos(position), Direction::Left)
+                            || s.0.contains(to_grid_pos(position), Direction::Right)
                     });
-                let v = visited.contains(&(position, Direction::Up))
-                    || visited.contains(&(position, Direction::Down))
+                let v = visited.contains(to_grid_pos(position), Direction::Up)
+                    || visited.contains(to_grid_pos(position), Direction::Down)
                     || supplemental.is_some_and(|s| {
-                        s.0.contains(&(position, Direction::Up))
-                            || s.0.contains(&(position, Direction::Down))
+                        s.0.contains(to_grid_pos(position), Direction::Up)
+                            || s.0.contains(to_grid_pos(position), Direction::Down)
                     });
                 match (h, v) {
                     (true, true) => print!("+"),
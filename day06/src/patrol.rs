@@ -0,0 +1,356 @@
+//! Guard patrol engine jumping from obstacle to obstacle.
+//!
+//! The obstacle tables of [`crate::parse_fancy`] are sorted, so the next
+//! obstacle in a direction is found with a binary search instead of walking
+//! the cells one by one. An extra obstruction can be added on top of them.
+
+use std::collections::HashSet;
+
+use miette::Result;
+
+use crate::{
+    document::{Direction, Position},
+    parse_fancy::parse,
+};
+
+/// Where a straight move of the guard ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    /// In front of an obstacle
+    Blocked(Position),
+    /// On the last cell before leaving the lab
+    Exit(Position),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patrol {
+    /// Each cell the guard walks on, in order, with the direction it entered
+    /// it (up for the start)
+    pub path: Vec<(Position, Direction)>,
+    /// `true` if the guard is stuck in a loop instead of leaving the lab
+    pub looping: bool,
+}
+
+impl Patrol {
+    /// Distinct positions of the path
+    pub fn visited(&self) -> HashSet<Position> {
+        self.path.iter().map(|&(pos, _)| pos).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lab {
+    /// Sorted obstacle rows, for each column
+    cols: Vec<Vec<usize>>,
+    /// Sorted obstacle columns, for each line
+    lines: Vec<Vec<usize>>,
+    start: Position,
+}
+
+impl Lab {
+    pub fn new(content: &[u8]) -> Result<Self> {
+        let (cols, lines, start) = parse(content)?;
+        Ok(Self { cols, lines, start })
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Route of the guard, from the start facing up, with an optional extra
+    /// obstruction
+    pub fn patrol(&self, extra: Option<Position>) -> Patrol {
+        let mut path = vec![(self.start, Direction::Up)];
+        let looping = self.walk(self.start, Direction::Up, extra, |from, to, dir| {
+            let mut position = from;
+            while position != to {
+                position += dir;
+                path.push((position, dir));
+            }
+        });
+        Patrol { path, looping }
+    }
+
+    /// Positions where a single extra obstruction makes the guard loop, in
+    /// the order the guard would first reach them
+    pub fn obstructions(&self) -> Vec<Position> {
+        let mut seen = HashSet::from([self.start]);
+        let mut obstructions = Vec::new();
+        self.walk(self.start, Direction::Up, None, |from, to, dir| {
+            let mut position = from;
+            while position != to {
+                let next = position + dir;
+                // The route up to the first visit of `next` doesn't go
+                // through it, so the guard can resume from here
+                if seen.insert(next) && self.walk(position, dir.right(), Some(next), |_, _, _| {}) {
+                    obstructions.push(next);
+                }
+                position = next;
+            }
+        });
+        obstructions
+    }
+
+    /// Move the guard straight from obstacle to obstacle, calling `segment`
+    /// with the start, end and direction of each move. Returns `true` if the
+    /// guard loops.
+    fn walk(
+        &self,
+        mut position: Position,
+        mut direction: Direction,
+        extra: Option<Position>,
+        mut segment: impl FnMut(Position, Position, Direction),
+    ) -> bool {
+        let mut turns = HashSet::new();
+        loop {
+            match self.jump(position, direction, extra) {
+                Stop::Blocked(stop) => {
+                    segment(position, stop, direction);
+                    position = stop;
+                    direction = direction.right();
+                    if !turns.insert((position, direction)) {
+                        return true;
+                    }
+                }
+                Stop::Exit(stop) => {
+                    segment(position, stop, direction);
+                    return false;
+                }
+            }
+        }
+    }
+
+    fn jump(&self, position: Position, direction: Direction, extra: Option<Position>) -> Stop {
+        let (obstacles, coord, size) = match direction {
+            Direction::Up | Direction::Down => {
+                (&self.cols[position.x], position.y, self.lines.len())
+            }
+            Direction::Left | Direction::Right => {
+                (&self.lines[position.y], position.x, self.cols.len())
+            }
+        };
+        let extra = extra.and_then(|extra| match direction {
+            Direction::Up | Direction::Down => (extra.x == position.x).then_some(extra.y),
+            Direction::Left | Direction::Right => (extra.y == position.y).then_some(extra.x),
+        });
+
+        // The guard is never on an obstacle, so `idx` splits the obstacles
+        // before and after it
+        let idx = obstacles.partition_point(|&obstacle| obstacle < coord);
+        // In front of an obstacle if `Ok`, on the edge of the lab if `Err`
+        let stop = match direction {
+            Direction::Up | Direction::Left => {
+                let obstacle = idx.checked_sub(1).map(|idx| obstacles[idx]);
+                let extra = extra.filter(|&extra| extra < coord);
+                obstacle.max(extra).map(|obstacle| obstacle + 1).ok_or(0)
+            }
+            Direction::Down | Direction::Right => {
+                let obstacle = obstacles.get(idx).copied();
+                let extra = extra.filter(|&extra| extra > coord);
+                [obstacle, extra]
+                    .into_iter()
+                    .flatten()
+                    .min()
+                    .map(|obstacle| obstacle - 1)
+                    .ok_or(size - 1)
+            }
+        };
+
+        let at = |coord| match direction {
+            Direction::Up | Direction::Down => Position {
+                y: coord,
+                ..position
+            },
+            Direction::Left | Direction::Right => Position {
+                x: coord,
+                ..position
+            },
+        };
+        match stop {
+            Ok(coord) => Stop::Blocked(at(coord)),
+            Err(coord) => Stop::Exit(at(coord)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{document::IPosition, parse_simple::parse, part1, part2};
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+"#;
+
+    /// Up the left column, then right out of the lab
+    const INPUT_EDGE: &[u8] = br#"
+......
+#.....
+......
+^.....
+......
+"#;
+
+    /// Stuck in a loop without any extra obstruction
+    const INPUT_LOOP: &[u8] = br#"
+.#....
+.....#
+......
+#^....
+....#.
+"#;
+    // cspell:enable
+
+    /// Walk cell by cell, returning `None` if the guard loops
+    fn walk_cells(content: &[u8], extra: Option<Position>) -> Option<Vec<(Position, Direction)>> {
+        let (mut grid, start) = parse(content).unwrap();
+        let width = content.iter().position(|&b| b == b'\n').unwrap() as isize;
+        let height = content
+            .split(|&b| b == b'\n')
+            .filter(|l| !l.is_empty())
+            .count() as isize;
+        if let Some(extra) = extra {
+            grid.insert(IPosition {
+                x: extra.x as isize,
+                y: extra.y as isize,
+            });
+        }
+        let to_pos = |pos: IPosition| Position {
+            x: pos.x as usize,
+            y: pos.y as usize,
+        };
+
+        let mut position = start;
+        let mut direction = Direction::Up;
+        let mut path = vec![(to_pos(position), direction)];
+        let mut seen = HashSet::from([(position, direction)]);
+        loop {
+            let next = position + direction;
+            if !(0..width).contains(&next.x) || !(0..height).contains(&next.y) {
+                return Some(path);
+            }
+            if grid.contains(&next) {
+                direction = direction.right();
+            } else {
+                position = next;
+                path.push((to_pos(position), direction));
+            }
+            if !seen.insert((position, direction)) {
+                return None;
+            }
+        }
+    }
+
+    fn obstructions_cells(content: &[u8]) -> Vec<Position> {
+        let (grid, start) = parse(content).unwrap();
+        let path = walk_cells(content, None).unwrap();
+        let mut obstructions = path
+            .iter()
+            .map(|&(pos, _)| pos)
+            .filter(|pos| {
+                let ipos = IPosition {
+                    x: pos.x as isize,
+                    y: pos.y as isize,
+                };
+                ipos != start && !grid.contains(&ipos)
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|&pos| walk_cells(content, Some(pos)).is_none())
+            .collect::<Vec<_>>();
+        obstructions.sort();
+        obstructions
+    }
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..];
+        let lab = Lab::new(input).unwrap();
+
+        let patrol = lab.patrol(None);
+        assert!(!patrol.looping);
+        assert_eq!(patrol.visited().len() as u64, part1::run(input).unwrap());
+        assert_eq!(Some(patrol.path), walk_cells(input, None));
+
+        let mut obstructions = lab.obstructions();
+        assert_eq!(obstructions.len() as u64, part2::run(input).unwrap());
+        obstructions.sort();
+        assert_eq!(
+            obstructions,
+            [
+                Position { x: 1, y: 8 },
+                Position { x: 3, y: 6 },
+                Position { x: 3, y: 8 },
+                Position { x: 6, y: 7 },
+                Position { x: 7, y: 7 },
+                Position { x: 7, y: 9 },
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_cells() {
+        for input in [INPUT_SAMPLE, INPUT_EDGE] {
+            let input = &input[1..];
+            let lab = Lab::new(input).unwrap();
+            let patrol = lab.patrol(None);
+            assert_eq!(Some(patrol.path), walk_cells(input, None));
+
+            let mut obstructions = lab.obstructions();
+            obstructions.sort();
+            assert_eq!(obstructions, obstructions_cells(input));
+
+            // Any single obstruction, even out of the path
+            let width = input.iter().position(|&b| b == b'\n').unwrap();
+            let height = input.len() / (width + 1);
+            for y in 0..height {
+                for x in 0..width {
+                    let extra = Position { x, y };
+                    let patrol = lab.patrol(Some(extra));
+                    match walk_cells(input, Some(extra)) {
+                        Some(cells) => {
+                            assert!(!patrol.looping, "{extra:?}");
+                            assert_eq!(patrol.path, cells, "{extra:?}");
+                        }
+                        None => assert!(patrol.looping, "{extra:?}"),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edge() {
+        let input = &INPUT_EDGE[1..];
+        let patrol = Lab::new(input).unwrap().patrol(None);
+        assert!(!patrol.looping);
+        assert_eq!(
+            patrol.path.last(),
+            Some(&(Position { x: 5, y: 2 }, Direction::Right))
+        );
+        assert_eq!(patrol.visited().len(), 7);
+    }
+
+    #[test]
+    fn looping() {
+        let input = &INPUT_LOOP[1..];
+        let lab = Lab::new(input).unwrap();
+        let patrol = lab.patrol(None);
+        assert!(patrol.looping);
+        assert_eq!(walk_cells(input, None), None);
+        assert_eq!(patrol.visited().len(), 10);
+    }
+}
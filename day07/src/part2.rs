@@ -1,3 +1,4 @@
+use common::digits::Digits;
 use miette::Result;
 
 use crate::parse::parse;
@@ -7,7 +8,7 @@ pub fn run(content: &[u8]) -> Result<u64> {
     let result: i64 = equations
         .into_iter()
         .filter_map(|equation| {
-            if count_solutions("".into(), equation.result, &equation.terms) > 0 {
+            if count_solutions(equation.result, &equation.terms) > 0 {
                 Some(equation.result)
             } else {
                 None
@@ -18,13 +19,11 @@ pub fn run(content: &[u8]) -> Result<u64> {
     Ok(result as u64)
 }
 
-fn count_solutions(ops: String, result: i64, terms: &[i64]) -> u64 {
+fn count_solutions(result: i64, terms: &[i64]) -> u64 {
     if terms.len() == 1 {
         if terms[0] == result {
-            //println!("OK: {} {ops}", terms[0]);
             1
         } else {
-            //println!("NOK: {} {ops}", terms[0]);
             0
         }
     } else {
@@ -33,23 +32,18 @@ fn count_solutions(ops: String, result: i64, terms: &[i64]) -> u64 {
         let term = term[0];
         let tmp_result = result - term;
         if tmp_result >= 0 {
-            count += count_solutions(format!("+ {term} {ops}"), tmp_result, rest);
+            count += count_solutions(tmp_result, rest);
         }
 
         let tmp_result = result / term;
         if (tmp_result * term) == result {
-            count += count_solutions(format!("* {term} {ops}"), tmp_result, rest);
+            count += count_solutions(tmp_result, rest);
         }
 
-        let result_str = format!("{result}").to_string();
-        let term_str = format!("{term}").to_string();
-        if let Some(trimmed) = result_str.strip_suffix(&term_str) {
-            if trimmed.is_empty() {
-                // there is no more result to match the remaining terms => no match
-            } else {
-                let new_result: i64 = trimmed.parse().unwrap();
-                count += count_solutions(format!("|| {term} {ops}"), new_result, rest);
-            }
+        // If there is no more result to match the remaining terms, this is not
+        // a match (`strip_suffix` returns `None`)
+        if let Some(new_result) = (result as u64).strip_suffix(term as u64) {
+            count += count_solutions(new_result as i64, rest);
         }
 
         count
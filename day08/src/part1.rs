@@ -1,5 +1,4 @@
-use std::collections::HashSet;
-
+use common::{bitset::GridBitSet, position::Position};
 use itertools::Itertools as _;
 use miette::Result;
 
@@ -8,7 +7,8 @@ use crate::parse::parse;
 pub fn run(content: &[u8]) -> Result<u64> {
     let (antennas, bounds) = parse(content)?;
 
-    let result: HashSet<_> = antennas
+    let mut result = GridBitSet::new(bounds.0.end as usize, bounds.1.end as usize);
+    antennas
         .values()
         .flat_map(|coord| {
             coord.iter().combinations(2).flat_map(|c| {
@@ -22,7 +22,9 @@ pub fn run(content: &[u8]) -> Result<u64> {
             })
         })
         .filter(|node| bounds.0.contains(&node.0) && bounds.1.contains(&node.1))
-        .collect();
+        .for_each(|node| {
+            result.insert(Position::new(node.0 as usize, node.1 as usize));
+        });
 
     // for y in bounds.1 {
     //     print!("    ");
@@ -33,7 +35,7 @@ pub fn run(content: &[u8]) -> Result<u64> {
     //                 .find_map(|coord| if *coord == (x, y) { Some(*freq) } else { None })
     //         }) {
     //             print!("{}", freq as char)
-    //         } else if result.contains(&(x, y)) {
+    //         } else if result.contains(Position::new(x as usize, y as usize)) {
     //             print!("#")
     //         } else {
     //             print!(".")
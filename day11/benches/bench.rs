@@ -1,7 +1,7 @@
 use common::read_input_u8;
 use day11::{
     part1, part2, part2_base_fx, part2_breadth, part2_fxhashmap, part2_inline, part2_log,
-    part2_successors, part2_vec,
+    part2_memo, part2_successors, part2_vec,
 };
 
 fn main() {
@@ -78,4 +78,11 @@ mod part2_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part2_fxhashmap::run(&content, 75).unwrap());
     }
+
+    #[divan::bench(name = "7_memo")]
+    fn memo(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part2_memo::run(&content, 75).unwrap());
+    }
 }
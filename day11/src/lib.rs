@@ -6,5 +6,6 @@ pub mod part2_breadth;
 pub mod part2_fxhashmap;
 pub mod part2_inline;
 pub mod part2_log;
+pub mod part2_memo;
 pub mod part2_successors;
 pub mod part2_vec;
@@ -1,5 +1,6 @@
 use std::collections::{BTreeMap, HashMap};
 
+use common::digits::Digits;
 use miette::Result;
 
 use crate::parse::parse;
@@ -34,17 +35,10 @@ fn expand(
     // println!("> Expanding {stone}@{blink_left}");
     let new_stones = if stone == 0 {
         vec![1]
+    } else if let Some((left, right)) = stone.split_half() {
+        vec![left, right]
     } else {
-        let str = format!("{}", stone).to_string();
-        let half_len = str.len() / 2;
-        if str.len() % 2 == 0 {
-            vec![
-                str[0..half_len].parse().unwrap(),
-                str[half_len..].parse().unwrap(),
-            ]
-        } else {
-            vec![stone * 2024]
-        }
+        vec![stone * 2024]
     };
     let count = if blink_left == 0 {
         new_stones.len()
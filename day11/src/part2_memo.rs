@@ -0,0 +1,62 @@
+// Same as `part2_log` but using `common::memo` and `common::digits` instead of
+// a hand-rolled cache and log10
+use common::{digits::Digits, memo::Memo};
+use miette::Result;
+
+use crate::parse::parse;
+
+pub fn run(content: &[u8], blinks: usize) -> Result<u64> {
+    let stones = parse(content)?;
+
+    // Key: stone number, blink count
+    // Value: number of stones
+    let mut memo = Memo::new();
+
+    let result: usize = stones
+        .iter()
+        .map(|stone| expand(*stone, blinks, &mut memo))
+        .sum();
+
+    Ok(result as u64)
+}
+
+fn expand(stone: u64, blink_left: usize, memo: &mut Memo<(u64, usize), usize>) -> usize {
+    if blink_left == 0 {
+        return 1;
+    }
+
+    memo.get_or_insert_with((stone, blink_left), |memo| {
+        let blink_left = blink_left - 1;
+        if stone == 0 {
+            expand(1, blink_left, memo)
+        } else if let Some((left, right)) = stone.split_half() {
+            expand(left, blink_left, memo) + expand(right, blink_left, memo)
+        } else {
+            expand(stone * 2024, blink_left, memo)
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"125 17"#;
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE;
+
+        assert_eq!(run(input, 75).unwrap(), 65601038650482);
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(INPUT_SAMPLE, 75).unwrap(),
+            crate::part2::run(INPUT_SAMPLE, 75).unwrap()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use common::read_input_u8;
-use day12::{part1, part1_neighbors, part2};
+use day12::{part1, part1_neighbors, part1_regions, part2, part2_regions};
 
 fn main() {
     // Run registered benchmarks.
@@ -22,6 +22,13 @@ mod part1_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part1_neighbors::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "2_regions")]
+    fn regions(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part1_regions::run(&content).unwrap());
+    }
 }
 
 mod part2_bench {
@@ -33,4 +40,11 @@ mod part2_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part2::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "1_regions")]
+    fn regions(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part2_regions::run(&content).unwrap());
+    }
 }
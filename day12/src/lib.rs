@@ -1,4 +1,6 @@
 pub mod parse;
 pub mod part1;
 pub mod part1_neighbors;
+pub mod part1_regions;
 pub mod part2;
+pub mod part2_regions;
@@ -0,0 +1,84 @@
+use miette::Result;
+
+use crate::parse::parse_u8;
+
+pub fn run(content: &[u8]) -> Result<u64> {
+    let grid = parse_u8(content)?;
+
+    let result = grid
+        .regions(|a, b| a == b)
+        .list
+        .iter()
+        .map(|region| region.area * region.perimeter)
+        .sum::<usize>();
+
+    Ok(result as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+"#;
+
+    const INPUT_EXAMPLE_1: &[u8] = br#"
+AAAA
+BBCD
+BBCC
+EEEC
+"#;
+
+    const INPUT_EXAMPLE_2: &[u8] = br#"
+OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO
+"#;
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 1930);
+    }
+
+    #[test]
+    fn example1() {
+        let input = &INPUT_EXAMPLE_1[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 140);
+    }
+
+    #[test]
+    fn example2() {
+        let input = &INPUT_EXAMPLE_2[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 772);
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part1::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
+        assert_eq!(
+            run(&INPUT_EXAMPLE_2[1..]).unwrap(),
+            crate::part1::run(&INPUT_EXAMPLE_2[1..]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use miette::Result;
+
+use crate::parse::parse_u8;
+
+pub fn run(content: &[u8]) -> Result<u64> {
+    let grid = parse_u8(content)?;
+
+    let result = grid
+        .regions(|a, b| a == b)
+        .list
+        .iter()
+        .map(|region| region.area * region.sides)
+        .sum::<usize>();
+
+    Ok(result as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+"#;
+
+    const INPUT_EXAMPLE_1: &[u8] = br#"
+AAAA
+BBCD
+BBCC
+EEEC
+"#;
+
+    const INPUT_EXAMPLE_2: &[u8] = br#"
+OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO
+"#;
+
+    const INPUT_EXAMPLE_3: &[u8] = br#"
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE
+"#;
+
+    const INPUT_EXAMPLE_4: &[u8] = br#"
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA
+"#;
+
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 1206);
+    }
+
+    #[test]
+    fn example1() {
+        let input = &INPUT_EXAMPLE_1[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 80);
+    }
+
+    #[test]
+    fn example2() {
+        let input = &INPUT_EXAMPLE_2[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 436);
+    }
+
+    #[test]
+    fn example3() {
+        let input = &INPUT_EXAMPLE_3[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 236);
+    }
+
+    #[test]
+    fn example4() {
+        let input = &INPUT_EXAMPLE_4[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 368);
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part2::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
+        assert_eq!(
+            run(&INPUT_EXAMPLE_4[1..]).unwrap(),
+            crate::part2::run(&INPUT_EXAMPLE_4[1..]).unwrap()
+        );
+    }
+}
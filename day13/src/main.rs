@@ -1,8 +1,8 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
+use day13::{part1, part2};
 use miette::Result;
 use std::path::PathBuf;
-use day13::{part1, part2};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -1,3 +1,4 @@
+use common::math::solve_2x2;
 use miette::Result;
 
 use crate::{machine::Machine, parse};
@@ -26,32 +27,22 @@ fn process_machine(machine: Machine) -> usize {
     //     n * A.x + m * B.x = M.x
     //     n * A.y + m * B.y = M.y
     // where n,m are the number of time button A, button B are pressed
-    //
-    // so (https://en.wikipedia.org/wiki/Cramer's_rule):
-    //    n = (B.x * M.y - M.x * B.y) / (B.x * A.y - A.x * B.y)
-    //    m = (A.x * M.y - M.x * A.y) / (A.x * B.y - B.x * A.y)
-    assert_ne!(
-        machine.btn_a.claw_move.x as f64 / machine.btn_a.claw_move.y as f64,
-        machine.btn_b.claw_move.x as f64 / machine.btn_b.claw_move.y as f64,
-    );
-
-    let ax = machine.btn_a.claw_move.x as f64;
-    let ay = machine.btn_a.claw_move.y as f64;
-    let bx = machine.btn_b.claw_move.x as f64;
-    let by = machine.btn_b.claw_move.y as f64;
-    let mx = machine.prize.x as f64 + 10000000000000.0;
-    let my = machine.prize.y as f64 + 10000000000000.0;
-
-    let n = (bx * my - mx * by) / (bx * ay - ax * by);
-    let m = mx / bx - n * ax / bx;
-
-    let token_a = n.round();
-    let token_b = m.round();
-
-    const EPSILON: f64 = 0.001;
-    if (token_a - n).abs() > EPSILON || (token_b - m).abs() > EPSILON {
+    let a = machine.btn_a.claw_move;
+    let b = machine.btn_b.claw_move;
+    assert_ne!(a.x * b.y, a.y * b.x);
+
+    let Some((token_a, token_b)) = solve_2x2(
+        [[a.x as i64, b.x as i64], [a.y as i64, b.y as i64]],
+        [
+            machine.prize.x as i64 + 10000000000000,
+            machine.prize.y as i64 + 10000000000000,
+        ],
+    ) else {
         // we can't get a whole number of steps => the prize cannot be reach
         return 0;
+    };
+    if token_a < 0 || token_b < 0 {
+        return 0;
     }
 
     token_a as usize * machine.btn_a.tokens + token_b as usize * machine.btn_b.tokens
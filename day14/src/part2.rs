@@ -1,3 +1,4 @@
+use common::math::{gcd, lcm};
 use miette::Result;
 
 use crate::parse::parse;
@@ -5,36 +6,22 @@ use crate::parse::parse;
 pub fn run(content: &[u8], tiles: (isize, isize), _seconds: isize) -> Result<u64> {
     let mut robots = parse(content)?;
 
-    // Because the grid width is prime and the horizontal velocity of a robot is
-    // less than that, the robot must occupy each column once before it cycles.
+    // Each robot cycles horizontally every `width / gcd(width, vel.x)` seconds,
+    // and vertically every `height / gcd(height, vel.y)` seconds. So the robot
+    // cycles every lcm of those, and the whole grid every lcm of all the robot
+    // cycles.
     //
-    // The same is true vertically.
-    //
-    // And because both the width and height of the grid are both primes, and
-    // different primes, a robot will need to occupy all the cells of the grid
-    // once before cycling.
-    //
-    // And this is true for all the robots.
-    //
-    // So the repeat cycle has to be width*height
-    //
-    // Formulas (maybe):
-    //   x cycle = width / gcd(width, vel.x)
-    //   y cycle = height / gcd(height, vel.y)
-    //   robot cycle = lcm(x cycle, y cycle)
-    //   grid cycle = lcm(all robot cycles)
-    //
-    // Code:
-    //   let cycle = robots
-    //       .iter()
-    //       .map(|r| {
-    //           let x_cycle = tiles.0 / tiles.0.gcd(&r.vel.0);
-    //           let y_cycle = tiles.1 / tiles.1.gcd(&r.vel.1);
-    //           x_cycle.lcm(&y_cycle)
-    //       })
-    //       .reduce(|acc, l| acc.lcm(&l));
-    //   println!("cycle: {cycle:?}");
-    let repeat_cycle = tiles.0 * tiles.1;
+    // With the puzzle's grid, width and height are different primes larger
+    // than the velocities, so this is width*height.
+    let repeat_cycle = robots
+        .iter()
+        .map(|r| {
+            let x_cycle = tiles.0 as i64 / gcd(tiles.0 as i64, r.vel.0 as i64);
+            let y_cycle = tiles.1 as i64 / gcd(tiles.1 as i64, r.vel.1 as i64);
+            lcm(x_cycle, y_cycle).expect("cycle overflow")
+        })
+        .try_fold(1, lcm)
+        .expect("cycle overflow") as isize;
     assert!(robots.iter().all(|r| {
         (r.pos.0 + repeat_cycle * r.vel.0).rem_euclid(tiles.0) == r.pos.0
             && (r.pos.1 + repeat_cycle * r.vel.1).rem_euclid(tiles.1) == r.pos.1
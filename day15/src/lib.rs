@@ -1,3 +1,6 @@
 pub mod parse;
 pub mod part1;
 pub mod part2;
+pub mod planner;
+pub mod play;
+pub mod warehouse;
@@ -1,8 +1,23 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
-use day15::{part1, part2};
-use miette::Result;
-use std::path::PathBuf;
+use day15::{
+    parse::{format_moves, parse, parse_grid},
+    part1, part2,
+    planner::{self, Target},
+    play::{Key, KeyParser, Session},
+    warehouse::{self, Warehouse},
+};
+use miette::{IntoDiagnostic, Result};
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    process::Stdio,
+    thread::sleep,
+    time::Duration,
+};
+
+/// Clear the terminal and move the cursor to the top left corner
+const CLEAR: &str = "\x1b[2J\x1b[H";
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -18,6 +33,71 @@ enum Command {
 
     /// Part 2
     Part2 { file: Option<PathBuf> },
+
+    /// Scale the warehouse, with boxes of any width, and print the final grid
+    Warehouse {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        scale: ScaleArgs,
+
+        /// Animate the moves in the terminal
+        #[arg(long)]
+        replay: bool,
+
+        /// Delay between two frames of the replay, in milliseconds
+        #[arg(short, long, default_value_t = 100)]
+        delay: u64,
+    },
+
+    /// Move the robot with the arrow keys, `u` to undo and `q` to quit. The
+    /// moves of the file are ignored.
+    Play {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        scale: ScaleArgs,
+    },
+
+    /// Search the shortest moves reaching a target layout, ignoring the moves
+    /// of the file
+    Plan {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        scale: ScaleArgs,
+
+        /// File with the target layout of the boxes, as a grid in the input
+        /// format. Only the `O` cells are used: the robot is optional, and
+        /// the moves, if any, are ignored.
+        #[arg(short, long, required_unless_present = "gps")]
+        target: Option<PathBuf>,
+
+        /// Target sum of the GPS coordinates of the boxes
+        #[arg(short, long, conflicts_with = "target")]
+        gps: Option<usize>,
+
+        /// Maximum number of layouts explored
+        #[arg(short = 'n', long = "max-nodes", default_value_t = 1_000_000)]
+        max_nodes: usize,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct ScaleArgs {
+    /// Number of tiles per cell of the input
+    #[arg(short, long, default_value_t = 2)]
+    scale: usize,
+
+    /// Width of the boxes, the scale if not set
+    #[arg(short, long)]
+    width: Option<usize>,
+}
+
+impl ScaleArgs {
+    fn width(&self) -> usize {
+        self.width.unwrap_or(self.scale)
+    }
 }
 
 fn main() -> Result<()> {
@@ -30,8 +110,124 @@ fn main() -> Result<()> {
         Some(Command::Part2 { file }) => {
             println!("Result: {}", part2::run(&read_input_u8!(file)?)?)
         }
+        Some(Command::Warehouse {
+            file,
+            scale,
+            replay: false,
+            ..
+        }) => {
+            let (grid, result) =
+                warehouse::run(&read_input_u8!(file)?, scale.scale, scale.width())?;
+            print!("{grid:?}");
+            println!("Result: {result}")
+        }
+        Some(Command::Warehouse {
+            file,
+            scale,
+            replay: true,
+            delay,
+        }) => replay(&read_input_u8!(file)?, &scale, Duration::from_millis(delay))?,
+        Some(Command::Play { file, scale }) => play(&read_input_u8!(file)?, &scale)?,
+        Some(Command::Plan {
+            file,
+            scale,
+            target,
+            gps,
+            max_nodes,
+        }) => {
+            let grid = parse_grid(&read_input_u8!(file)?)?;
+            let warehouse = Warehouse::new(&grid, scale.scale, scale.width())?;
+            let target = match (target, gps) {
+                (Some(target), _) => {
+                    Target::from_grid(&parse_grid(&read_input_u8!(Some(target))?)?, scale.scale)
+                }
+                (None, Some(gps)) => Target::Gps(gps),
+                (None, None) => unreachable!("required by clap"),
+            };
+            match planner::plan(&warehouse, &target, max_nodes)? {
+                Some(moves) => {
+                    println!("{}", format_moves(&moves));
+                    println!("Result: {}", moves.len())
+                }
+                None => println!("The target can't be reached"),
+            }
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
     Ok(())
 }
+
+fn replay(content: &[u8], scale: &ScaleArgs, delay: Duration) -> Result<()> {
+    let (grid, moves) = parse(content)?;
+    let warehouse = Warehouse::new(&grid, scale.scale, scale.width())?;
+    print!("{CLEAR}{:?}", warehouse.grid);
+    for (idx, step) in warehouse.steps(&moves).enumerate() {
+        let step = step?;
+        sleep(delay);
+        print!("{CLEAR}{:?}", step.warehouse.grid);
+        println!(
+            "Move {}/{}: {:?}{}",
+            idx + 1,
+            moves.len(),
+            step.direction,
+            if step.moved { "" } else { " (blocked)" }
+        );
+        std::io::stdout().flush().into_diagnostic()?;
+    }
+    Ok(())
+}
+
+fn play(content: &[u8], scale: &ScaleArgs) -> Result<()> {
+    let grid = parse_grid(content)?;
+    let mut session = Session::new(Warehouse::new(&grid, scale.scale, scale.width())?);
+    let mut parser = KeyParser::default();
+
+    // Read the keys as soon as they are pressed, if the terminal allows it
+    let _terminal = RawTerminal::new();
+    let mut message = String::new();
+    loop {
+        print!("{CLEAR}{:?}", session.warehouse().grid);
+        println!("GPS: {}   {message}", session.warehouse().gps());
+        println!("arrows or ^ v < > to move, u to undo, q to quit");
+        std::io::stdout().flush().into_diagnostic()?;
+
+        let mut byte = [0];
+        if std::io::stdin().read(&mut byte).into_diagnostic()? == 0 {
+            break;
+        }
+        message = match parser.feed(byte[0]) {
+            Some(Key::Move(dir)) if !session.push(dir)? => format!("{dir:?} is blocked"),
+            Some(Key::Undo) if !session.undo() => "nothing to undo".to_string(),
+            Some(Key::Quit) => break,
+            _ => String::new(),
+        };
+    }
+    println!("Moves: {}", session.moves());
+    Ok(())
+}
+
+/// Disable the line buffering and echo of the terminal until dropped
+struct RawTerminal(bool);
+
+impl RawTerminal {
+    fn new() -> Self {
+        Self(stty(&["-icanon", "-echo"]))
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        if self.0 {
+            stty(&["icanon", "echo"]);
+        }
+    }
+}
+
+fn stty(args: &[&str]) -> bool {
+    std::process::Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
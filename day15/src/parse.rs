@@ -36,6 +36,19 @@ pub fn parse(content: &[u8]) -> Result<(Grid<Cell>, Vec<Direction>), AdventError
     .parse(content)?)
 }
 
+/// Moves in the input format, the reverse of the moves parser
+pub fn format_moves<'a>(moves: impl IntoIterator<Item = &'a Direction>) -> String {
+    moves
+        .into_iter()
+        .map(|dir| match dir {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        })
+        .collect()
+}
+
 pub fn parse_grid(content: &[u8]) -> Result<Grid<Cell>, AdventError> {
     Ok(trace("parse_grid", terminated(grid_parser, rest)).parse(content)?)
 }
@@ -1,91 +1,28 @@
-use std::iter::successors;
+use miette::Result;
 
-use common::position::{Direction, Grid, Position};
-use miette::{miette, Result};
-
-use crate::parse::{parse, Cell};
+use crate::warehouse;
 
 pub fn run(content: &[u8]) -> Result<u64> {
-    let grid = parse_and_apply(content)?;
-
-    let result: usize = (0..grid.height())
-        .flat_map(|y| (0..grid.width()).map(move |x| Position::new(x, y)))
-        .enumerate()
-        .map(|(idx, position)| {
-            if unsafe { grid.get_idx_unchecked(idx) } == Cell::Box {
-                position.y * 100 + position.x
-            } else {
-                0
-            }
-        })
-        .sum();
-
-    Ok(result as u64)
-}
-
-fn parse_and_apply(content: &[u8]) -> Result<Grid<Cell>> {
-    let (mut grid, moves) = parse(content)?;
-    apply_moves(&mut grid, &moves)?;
-    Ok(grid)
-}
-
-fn apply_moves(grid: &mut Grid<Cell>, moves: &[Direction]) -> Result<()> {
-    // Find the robot and replace it with an empty cell
-    let width = grid.width();
-    let Some(mut position) = grid
-        .iter_mut()
-        .enumerate()
-        .find(|(_, cell)| **cell == Cell::Robot)
-        .map(|(idx, cell)| {
-            *cell = Cell::Empty;
-            Position::from_index(idx, width)
-        })
-    else {
-        return Err(miette!("No starting position"));
-    };
-
-    // Apply the move
-    moves.iter().copied().for_each(|dir| {
-        // New bot position
-        let new_position = position + dir;
-
-        // Find the next non-box cell in the direction of the move
-        let next_non_box = successors(Some(new_position), |pos| Some(*pos + dir))
-            .find(|pos| !grid.get(*pos).is_some_and(|cell| cell == Cell::Box))
-            .unwrap();
-
-        // If we found a bunch of boxes, try to move them (just swap the start
-        // and end positions)
-        if next_non_box != new_position {
-            let cell = grid.get_mut(next_non_box).expect("out of grid bound");
-            if *cell == Cell::Empty {
-                // Swap the cell
-                *cell = Cell::Box;
-                *grid.get_mut(new_position).expect("out of grid bound") = Cell::Empty;
-            }
-        }
-
-        if *grid.get_mut(new_position).expect("out of grid bound") != Cell::Empty {
-            // New position is not empty => can't move the bot
-            return;
-        }
-
-        // Set the new bot's position
-        position = new_position;
-    });
-
-    // Set the bot
-    *grid.get_mut(position).unwrap() = Cell::Robot;
-
-    Ok(())
+    let (_, result) = warehouse::run(content, 1, 1)?;
+    Ok(result)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::parse::parse_grid;
+    use common::position::Grid;
+
+    use crate::{
+        parse::parse_grid,
+        warehouse::{Tile, Warehouse},
+    };
 
     use super::*;
 
+    fn parse_and_apply(content: &[u8]) -> Result<Grid<Tile>> {
+        let (grid, _) = warehouse::run(content, 1, 1)?;
+        Ok(grid)
+    }
+
     // cspell:disable
     const INPUT_SAMPLE: &[u8] = br#"
 ##########
@@ -154,7 +91,9 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
 "#[1..];
         assert_eq!(
             parse_and_apply(input).unwrap(),
-            parse_grid(result_grid).unwrap()
+            Warehouse::new(&parse_grid(result_grid).unwrap(), 1, 1)
+                .unwrap()
+                .grid
         );
     }
 
@@ -176,7 +115,9 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
 
         assert_eq!(
             parse_and_apply(input).unwrap(),
-            parse_grid(result_grid).unwrap()
+            Warehouse::new(&parse_grid(result_grid).unwrap(), 1, 1)
+                .unwrap()
+                .grid
         );
     }
 
@@ -0,0 +1,293 @@
+//! Sokoban-style planner: the shortest sequence of moves that reaches a
+//! target layout, found with a breadth-first search over the positions of
+//! the robot and the boxes.
+
+use common::position::{Direction, Grid, Position};
+use miette::{miette, Result};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    parse::Cell,
+    warehouse::{Tile, Warehouse},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Left tile of each box, in any order
+    Boxes(Vec<Position>),
+    /// Sum of the GPS coordinates of the boxes
+    Gps(usize),
+}
+
+impl Target {
+    /// Boxes of a grid in the input format, scaled like [`Warehouse::new`].
+    /// Unlike a warehouse, the grid doesn't need a robot.
+    pub fn from_grid(grid: &Grid<Cell>, scale: usize) -> Self {
+        let boxes = grid
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == Cell::Box)
+            .map(|(idx, _)| {
+                let pos = Position::from_index(idx, grid.width());
+                Position {
+                    x: pos.x * scale,
+                    ..pos
+                }
+            })
+            .collect();
+        Self::Boxes(boxes)
+    }
+}
+
+/// Robot position, and left tile and width of each box in reading order
+type Key = (Position, Vec<(Position, u8)>);
+
+/// Shortest sequence of moves from `warehouse` to `target`, using the push
+/// rules of [`Warehouse::step`]. Returns `None` if the target can't be
+/// reached (e.g. it doesn't have as many boxes as the warehouse), and fails
+/// if more than `max_nodes` layouts were explored.
+pub fn plan(
+    warehouse: &Warehouse,
+    target: &Target,
+    max_nodes: usize,
+) -> Result<Option<Vec<Direction>>> {
+    let target_boxes = match target {
+        Target::Boxes(boxes) => {
+            let mut boxes = boxes.clone();
+            boxes.sort_by_key(|pos| (pos.y, pos.x));
+            boxes
+        }
+        Target::Gps(_) => Vec::new(),
+    };
+    // Boxes are never created nor destroyed
+    if matches!(target, Target::Boxes(_)) && target_boxes.len() != warehouse.boxes().len() {
+        return Ok(None);
+    }
+    let reached = |(_, boxes): &Key| match target {
+        Target::Boxes(_) => boxes.iter().map(|(pos, _)| pos).eq(&target_boxes),
+        Target::Gps(gps) => {
+            boxes
+                .iter()
+                .map(|(pos, _)| pos.y * 100 + pos.x)
+                .sum::<usize>()
+                == *gps
+        }
+    };
+
+    // Only the walls, the robot and the boxes are placed back for each layout
+    let walls = Grid::new(
+        warehouse
+            .grid
+            .iter()
+            .map(|&tile| match tile {
+                Tile::Wall => Tile::Wall,
+                _ => Tile::Empty,
+            })
+            .collect(),
+        warehouse.grid.width(),
+        warehouse.grid.height(),
+    );
+    let build = |(robot, boxes): &Key| {
+        let mut grid = walls.clone();
+        *grid.get_mut(*robot).expect("out of grid bound") = Tile::Robot;
+        for &(pos, width) in boxes {
+            for offset in 0..width {
+                let tile = Position {
+                    x: pos.x + offset as usize,
+                    ..pos
+                };
+                *grid.get_mut(tile).expect("out of grid bound") = Tile::Box { offset, width };
+            }
+        }
+        Warehouse {
+            grid,
+            robot: *robot,
+        }
+    };
+
+    let start = key(warehouse);
+    if reached(&start) {
+        return Ok(Some(Vec::new()));
+    }
+    let mut seen = FxHashSet::default();
+    seen.insert(start.clone());
+    // Layouts in the order they are found, with their parent and the move
+    // from it
+    let mut nodes = vec![(start, usize::MAX, Direction::Up)];
+    let mut idx = 0;
+    while idx < nodes.len() {
+        let current = build(&nodes[idx].0);
+        for &dir in Direction::list() {
+            let mut next = current.clone();
+            if !next.step(dir)? {
+                continue;
+            }
+            let next = key(&next);
+            if !seen.insert(next.clone()) {
+                continue;
+            }
+            let done = reached(&next);
+            nodes.push((next, idx, dir));
+            if done {
+                return Ok(Some(moves(&nodes)));
+            }
+            if nodes.len() > max_nodes {
+                return Err(miette!("no plan found within {max_nodes} nodes"));
+            }
+        }
+        idx += 1;
+    }
+
+    Ok(None)
+}
+
+fn key(warehouse: &Warehouse) -> Key {
+    let boxes = warehouse
+        .boxes()
+        .into_iter()
+        .map(|pos| match warehouse.grid.get(pos) {
+            Some(Tile::Box { width, .. }) => (pos, width),
+            _ => unreachable!("boxes are box tiles"),
+        })
+        .collect();
+    (warehouse.robot, boxes)
+}
+
+/// Moves from the first node to the last one
+fn moves(nodes: &[(Key, usize, Direction)]) -> Vec<Direction> {
+    let mut moves = Vec::new();
+    let mut idx = nodes.len() - 1;
+    while idx != 0 {
+        let (_, parent, dir) = nodes[idx];
+        moves.push(dir);
+        idx = parent;
+    }
+    moves.reverse();
+    moves
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{parse, parse_grid};
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_ROOM: &[u8] = br#"
+#######
+#.....#
+#.O@..#
+#.....#
+#######
+"#;
+
+    const INPUT_CORNER: &[u8] = br#"
+#####
+#O..#
+#..@#
+#####
+"#;
+
+    const INPUT_TARGET: &[u8] = br#"
+#######
+#.....#
+#...O.#
+#.....#
+#######
+"#;
+
+    const INPUT_EXAMPLE: &[u8] = br#"
+#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^
+"#;
+    // cspell:enable
+
+    fn check(warehouse: &Warehouse, target: &Target, moves: &[Direction]) {
+        let mut warehouse = warehouse.clone();
+        warehouse.apply_moves(moves).unwrap();
+        match target {
+            Target::Boxes(boxes) => {
+                let mut boxes = boxes.clone();
+                boxes.sort_by_key(|pos| (pos.y, pos.x));
+                assert_eq!(warehouse.boxes(), boxes);
+            }
+            Target::Gps(gps) => assert_eq!(warehouse.gps(), *gps),
+        }
+    }
+
+    #[test]
+    fn narrow() {
+        let warehouse = Warehouse::new(&parse_grid(&INPUT_ROOM[1..]).unwrap(), 1, 1).unwrap();
+        // Around the box, then two pushes right
+        let target = Target::Boxes(vec![Position::new(4, 2)]);
+        let moves = plan(&warehouse, &target, 1000).unwrap().unwrap();
+        assert_eq!(moves.len(), 6);
+        check(&warehouse, &target, &moves);
+
+        let target = Target::Gps(204);
+        let moves = plan(&warehouse, &target, 1000).unwrap().unwrap();
+        assert_eq!(moves.len(), 6);
+        check(&warehouse, &target, &moves);
+
+        let target = Target::Gps(warehouse.gps());
+        assert_eq!(plan(&warehouse, &target, 1000).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn wide() {
+        let warehouse = Warehouse::new(&parse_grid(&INPUT_ROOM[1..]).unwrap(), 2, 2).unwrap();
+        // Around the box, then four pushes right
+        let target = Target::Boxes(vec![Position::new(8, 2)]);
+        let moves = plan(&warehouse, &target, 1000).unwrap().unwrap();
+        assert_eq!(moves.len(), 9);
+        check(&warehouse, &target, &moves);
+
+        // Same final layout as the example moves, in fewer moves
+        let (grid, example) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let warehouse = Warehouse::new(&grid, 2, 2).unwrap();
+        let mut expected = warehouse.clone();
+        expected.apply_moves(&example).unwrap();
+        let target = Target::Boxes(expected.boxes());
+        let moves = plan(&warehouse, &target, 100_000).unwrap().unwrap();
+        assert!(moves.len() < example.len());
+        check(&warehouse, &target, &moves);
+    }
+
+    #[test]
+    fn target_grid() {
+        let grid = parse_grid(&INPUT_TARGET[1..]).unwrap();
+        assert_eq!(
+            Target::from_grid(&grid, 1),
+            Target::Boxes(vec![Position::new(4, 2)])
+        );
+
+        let warehouse = Warehouse::new(&parse_grid(&INPUT_ROOM[1..]).unwrap(), 2, 1).unwrap();
+        let target = Target::from_grid(&grid, 2);
+        assert_eq!(target, Target::Boxes(vec![Position::new(8, 2)]));
+        let moves = plan(&warehouse, &target, 1000).unwrap().unwrap();
+        check(&warehouse, &target, &moves);
+    }
+
+    #[test]
+    fn unreachable() {
+        let warehouse = Warehouse::new(&parse_grid(&INPUT_CORNER[1..]).unwrap(), 1, 1).unwrap();
+        let target = Target::Boxes(vec![Position::new(2, 1)]);
+        assert_eq!(plan(&warehouse, &target, 1000).unwrap(), None);
+
+        let warehouse = Warehouse::new(&parse_grid(&INPUT_ROOM[1..]).unwrap(), 1, 1).unwrap();
+        let target = Target::Boxes(vec![Position::new(4, 2)]);
+        assert!(plan(&warehouse, &target, 5).is_err());
+
+        // Not the same number of boxes, found without exploring
+        let target = Target::Boxes(vec![Position::new(4, 2), Position::new(4, 3)]);
+        assert_eq!(plan(&warehouse, &target, 5).unwrap(), None);
+        assert_eq!(plan(&warehouse, &Target::Boxes(vec![]), 5).unwrap(), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Interactive play in the warehouse, with undo.
+
+use common::position::Direction;
+use miette::Result;
+
+use crate::{parse::format_moves, warehouse::Warehouse};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Move(Direction),
+    Undo,
+    Quit,
+}
+
+/// Decode the keys from the bytes read on a terminal: the arrow keys (as
+/// `ESC [ A` to `ESC [ D`), the move characters `^`, `v`, `<`, `>`, `u` to
+/// undo and `q` to quit. Other bytes are ignored.
+#[derive(Debug, Default)]
+pub struct KeyParser {
+    /// Number of bytes of an escape sequence already read
+    escape: u8,
+}
+
+impl KeyParser {
+    pub fn feed(&mut self, byte: u8) -> Option<Key> {
+        match (self.escape, byte) {
+            (_, 0x1b) => {
+                self.escape = 1;
+                None
+            }
+            (1, b'[') => {
+                self.escape = 2;
+                None
+            }
+            (2, _) => {
+                self.escape = 0;
+                match byte {
+                    b'A' => Some(Key::Move(Direction::Up)),
+                    b'B' => Some(Key::Move(Direction::Down)),
+                    b'C' => Some(Key::Move(Direction::Right)),
+                    b'D' => Some(Key::Move(Direction::Left)),
+                    _ => None,
+                }
+            }
+            _ => {
+                self.escape = 0;
+                match byte {
+                    b'^' => Some(Key::Move(Direction::Up)),
+                    b'v' => Some(Key::Move(Direction::Down)),
+                    b'>' => Some(Key::Move(Direction::Right)),
+                    b'<' => Some(Key::Move(Direction::Left)),
+                    b'u' => Some(Key::Undo),
+                    b'q' => Some(Key::Quit),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Warehouse with the history of the moves that changed it
+#[derive(Debug, Clone)]
+pub struct Session {
+    warehouse: Warehouse,
+    history: Vec<(Direction, Warehouse)>,
+}
+
+impl Session {
+    pub fn new(warehouse: Warehouse) -> Self {
+        Self {
+            warehouse,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn warehouse(&self) -> &Warehouse {
+        &self.warehouse
+    }
+
+    /// Moves that changed the warehouse, in the input format
+    pub fn moves(&self) -> String {
+        format_moves(self.history.iter().map(|(dir, _)| dir))
+    }
+
+    /// Move the robot, returns `false` if a wall blocked it, in which case
+    /// nothing is recorded
+    pub fn push(&mut self, dir: Direction) -> Result<bool> {
+        let before = self.warehouse.clone();
+        let moved = self.warehouse.step(dir)?;
+        if moved {
+            self.history.push((dir, before));
+        }
+        Ok(moved)
+    }
+
+    /// Revert the last move, returns `false` if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((_, before)) => {
+                self.warehouse = before;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::parse;
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_EXAMPLE: &[u8] = br#"
+#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^
+"#;
+    // cspell:enable
+
+    #[test]
+    fn keys() {
+        let mut parser = KeyParser::default();
+        let keys = b"\x1b[A\x1b[Dx<u\x1b[Z>\x1bq"
+            .iter()
+            .filter_map(|&byte| parser.feed(byte))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                Key::Move(Direction::Up),
+                Key::Move(Direction::Left),
+                Key::Move(Direction::Left),
+                Key::Undo,
+                Key::Move(Direction::Right),
+                Key::Quit,
+            ]
+        );
+    }
+
+    #[test]
+    fn undo() {
+        let (grid, moves) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let start = Warehouse::new(&grid, 2, 2).unwrap();
+        let mut session = Session::new(start.clone());
+        for &dir in &moves {
+            session.push(dir).unwrap();
+        }
+        let mut expected = start.clone();
+        expected.apply_moves(&moves).unwrap();
+        assert_eq!(session.warehouse(), &expected);
+        // The blocked push up is not recorded
+        assert_eq!(session.moves(), "<vv<<^<<^^");
+
+        assert!(session.undo());
+        assert!(session.undo());
+        assert!(session.push(Direction::Up).unwrap());
+        assert_eq!(session.moves(), "<vv<<^<<^");
+
+        while session.undo() {}
+        assert_eq!(session.warehouse(), &start);
+        assert_eq!(session.moves(), "");
+    }
+}
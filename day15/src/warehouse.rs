@@ -0,0 +1,314 @@
+//! Box-pushing engine for any box width and scale factor.
+//!
+//! Each cell of the input is scaled horizontally into `scale` tiles. A box
+//! becomes `width` box tiles followed by empty tiles, the robot is followed
+//! by empty tiles, and walls fill all the tiles.
+
+use std::cmp::Reverse;
+
+use common::{
+    bitset::GridBitSet,
+    position::{Direction, Grid, Position},
+};
+use miette::{miette, Result};
+
+use crate::parse::{parse, Cell};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    #[default]
+    Empty,
+    Wall,
+    /// Tile `offset` (from the left) of a box `width` tiles wide
+    Box {
+        offset: u8,
+        width: u8,
+    },
+    Robot,
+}
+impl From<Tile> for &str {
+    fn from(value: Tile) -> Self {
+        match value {
+            Tile::Empty => ".",
+            Tile::Wall => "#",
+            Tile::Box { width: 1, .. } => "O",
+            Tile::Box { offset: 0, .. } => "[",
+            Tile::Box { offset, width } if offset + 1 == width => "]",
+            Tile::Box { .. } => "=",
+            Tile::Robot => "@",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warehouse {
+    pub grid: Grid<Tile>,
+    pub robot: Position,
+}
+
+impl Warehouse {
+    /// Fails if `width` is not in `1..=scale`, or if there is not exactly
+    /// one robot
+    pub fn new(grid: &Grid<Cell>, scale: usize, width: usize) -> Result<Self> {
+        if width == 0 || width > scale || width > u8::MAX as usize {
+            return Err(miette!("invalid box width {width} for scale {scale}"));
+        }
+        let data = grid
+            .iter()
+            .flat_map(|&cell| {
+                (0..scale).map(move |n| match cell {
+                    Cell::Wall => Tile::Wall,
+                    Cell::Box if n < width => Tile::Box {
+                        offset: n as u8,
+                        width: width as u8,
+                    },
+                    Cell::Robot if n == 0 => Tile::Robot,
+                    _ => Tile::Empty,
+                })
+            })
+            .collect::<Vec<_>>();
+        let grid = Grid::new(data, grid.width() * scale, grid.height());
+
+        let mut robots = grid
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile == Tile::Robot)
+            .map(|(idx, _)| Position::from_index(idx, grid.width()));
+        let robot = robots
+            .next()
+            .ok_or_else(|| miette!("No starting position"))?;
+        if robots.next().is_some() {
+            return Err(miette!("more than one bot"));
+        }
+
+        Ok(Self { grid, robot })
+    }
+
+    /// Move the robot once, pushing the boxes in front of it. Returns `false`
+    /// if a wall blocks the move.
+    pub fn step(&mut self, dir: Direction) -> Result<bool> {
+        let next = self.robot + dir;
+        let Some(boxes) = get_boxes(&self.grid, next, dir)? else {
+            return Ok(false);
+        };
+
+        // Sort the box tiles so we don't clobber the result when we move them
+        let mut boxes = boxes.iter().collect::<Vec<_>>();
+        match dir {
+            Direction::Up => boxes.sort_by_key(|pos| pos.y),
+            Direction::Right => boxes.sort_by_key(|pos| Reverse(pos.x)),
+            Direction::Down => boxes.sort_by_key(|pos| Reverse(pos.y)),
+            Direction::Left => boxes.sort_by_key(|pos| pos.x),
+        }
+        boxes.into_iter().for_each(|pos| {
+            self.grid.swap(pos, pos + dir);
+        });
+
+        self.grid.swap(self.robot, next);
+        self.robot = next;
+        Ok(true)
+    }
+
+    pub fn apply_moves(&mut self, moves: &[Direction]) -> Result<()> {
+        moves.iter().try_for_each(|&dir| self.step(dir).map(|_| ()))
+    }
+
+    /// Iterator over the states of the warehouse after each move
+    pub fn steps(self, moves: &[Direction]) -> Steps<'_> {
+        Steps {
+            warehouse: self,
+            moves: moves.iter(),
+        }
+    }
+
+    /// Left tile of each box, in reading order
+    pub fn boxes(&self) -> Vec<Position> {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| matches!(tile, Tile::Box { offset: 0, .. }))
+            .map(|(idx, _)| Position::from_index(idx, self.grid.width()))
+            .collect()
+    }
+
+    /// Sum of the GPS coordinates of the boxes, from their left tile
+    pub fn gps(&self) -> usize {
+        self.boxes()
+            .into_iter()
+            .map(|position| position.y * 100 + position.x)
+            .sum()
+    }
+}
+
+/// State of the warehouse after a move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub direction: Direction,
+    /// `false` if a wall blocked the move
+    pub moved: bool,
+    pub warehouse: Warehouse,
+}
+
+pub struct Steps<'a> {
+    warehouse: Warehouse,
+    moves: std::slice::Iter<'a, Direction>,
+}
+
+impl Iterator for Steps<'_> {
+    type Item = Result<Step>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &direction = self.moves.next()?;
+        Some(self.warehouse.step(direction).map(|moved| Step {
+            direction,
+            moved,
+            warehouse: self.warehouse.clone(),
+        }))
+    }
+}
+
+/// Final grid and GPS sum after applying the moves, with the input scaled
+/// by `scale` and boxes `width` tiles wide
+pub fn run(content: &[u8], scale: usize, width: usize) -> Result<(Grid<Tile>, u64)> {
+    let (grid, moves) = parse(content)?;
+    let mut warehouse = Warehouse::new(&grid, scale, width)?;
+    warehouse.apply_moves(&moves)?;
+    let gps = warehouse.gps() as u64;
+    Ok((warehouse.grid, gps))
+}
+
+/// All the box tiles that would move if something pushes `start` towards
+/// `dir`, `None` if they are blocked by a wall
+pub fn get_boxes(grid: &Grid<Tile>, start: Position, dir: Direction) -> Result<Option<GridBitSet>> {
+    let mut to_check = vec![start];
+    let mut boxes = GridBitSet::for_grid(grid);
+
+    while let Some(position) = to_check.pop() {
+        if boxes.contains(position) {
+            // already checked
+            continue;
+        }
+        match grid.get(position) {
+            Some(Tile::Empty) => {}
+            Some(Tile::Wall) => return Ok(None), // can't move
+            Some(Tile::Box { offset, width }) => {
+                let left = position.x - offset as usize;
+                for x in left..left + width as usize {
+                    let tile = Position { x, ..position };
+                    boxes.insert(tile);
+                    to_check.push(tile + dir);
+                }
+            }
+            Some(Tile::Robot) => return Err(miette!("more than one bot")),
+            None => return Err(miette!("out of bound")),
+        }
+    }
+
+    Ok(Some(boxes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_EXAMPLE: &[u8] = br#"
+#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^
+"#;
+    // cspell:enable
+
+    #[test]
+    fn scale() {
+        let (grid, _) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let warehouse = Warehouse::new(&grid, 3, 3).unwrap();
+        const EXPECTED: &str = r#"
+  #####################
+  ###.........###...###
+  ###...............###
+  ###......[=][=]@..###
+  ###......[=]......###
+  ###...............###
+  #####################
+"#;
+        assert_eq!(format!("{:?}", warehouse.grid), EXPECTED);
+        assert_eq!(warehouse.robot, Position::new(15, 3));
+
+        let warehouse = Warehouse::new(&grid, 3, 1).unwrap();
+        const EXPECTED_NARROW: &str = r#"
+  #####################
+  ###.........###...###
+  ###...............###
+  ###......O..O..@..###
+  ###......O........###
+  ###...............###
+  #####################
+"#;
+        assert_eq!(format!("{:?}", warehouse.grid), EXPECTED_NARROW);
+
+        assert!(Warehouse::new(&grid, 2, 3).is_err());
+        assert!(Warehouse::new(&grid, 2, 0).is_err());
+    }
+
+    #[test]
+    fn wide_boxes() {
+        let (grid, moves) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let mut warehouse = Warehouse::new(&grid, 3, 3).unwrap();
+        warehouse.apply_moves(&moves).unwrap();
+        const EXPECTED: &str = r#"
+  #####################
+  ###.......@.###...###
+  ###........[=]....###
+  ###....[=]........###
+  ###......[=]......###
+  ###...............###
+  #####################
+"#;
+        assert_eq!(format!("{:?}", warehouse.grid), EXPECTED);
+        assert_eq!(warehouse.gps(), 211 + 307 + 409);
+    }
+
+    #[test]
+    fn steps() {
+        let (grid, moves) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let warehouse = Warehouse::new(&grid, 2, 2).unwrap();
+        let mut expected = warehouse.clone();
+        let steps = warehouse.steps(&moves).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(steps.len(), moves.len());
+        for (step, &dir) in steps.iter().zip(&moves) {
+            let before = expected.robot;
+            assert_eq!(step.moved, expected.step(dir).unwrap());
+            assert_eq!(step.moved, before != step.warehouse.robot);
+            assert_eq!(step.warehouse, expected);
+        }
+        // Only the second push up is blocked, by the wall above the boxes
+        let blocked = (0..steps.len())
+            .filter(|&idx| !steps[idx].moved)
+            .collect::<Vec<_>>();
+        assert_eq!(blocked, [6]);
+    }
+
+    #[test]
+    fn blocked() {
+        let (grid, _) = parse(&INPUT_EXAMPLE[1..]).unwrap();
+        let mut warehouse = Warehouse::new(&grid, 2, 2).unwrap();
+        let before = warehouse.clone();
+        // Three tiles to the right, then the wall
+        assert!(warehouse.step(Direction::Right).unwrap());
+        assert!(!warehouse.step(Direction::Right).unwrap());
+        assert_eq!(warehouse.robot, before.robot + Direction::Right);
+
+        // Back, then pushing the two boxes three tiles left
+        for _ in 0..4 {
+            assert!(warehouse.step(Direction::Left).unwrap());
+        }
+        assert_eq!(warehouse.gps(), before.gps() - 2 * 3);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod maze;
 pub mod parse;
 pub mod part1;
 pub mod part2;
@@ -5,3 +6,4 @@ pub mod part2_fxhashset;
 pub mod part2_inline;
 pub mod part2_nil;
 pub mod part2_simple;
+pub mod paths;
@@ -1,7 +1,10 @@
 use clap::{Parser, Subcommand};
-use common::read_input_u8;
-use day16::{part1, part2};
-use miette::Result;
+use common::{position::Direction, read_input_u8};
+use day16::{
+    maze::{Costs, Maze, Options},
+    part1, part2,
+};
+use miette::{miette, Result};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -14,22 +17,99 @@ struct Args {
 #[command(rename_all = "lower")]
 enum Command {
     /// Part 1
-    Part1 { file: Option<PathBuf> },
+    Part1 {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        maze: MazeArgs,
+    },
 
     /// Part 2
     Part2 { file: Option<PathBuf> },
+
+    /// Draw the best paths with their turns and steps
+    Paths {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        maze: MazeArgs,
+
+        /// List the k cheapest distinct paths instead of all the best ones
+        #[arg(short, long)]
+        k: Option<usize>,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct MazeArgs {
+    /// Cost of a 90 degrees rotation
+    #[arg(short, long, default_value_t = 1000)]
+    rotation: usize,
+
+    /// Cost of a step forward
+    #[arg(short, long, default_value_t = 1)]
+    translation: usize,
+
+    /// Direction faced at the start: up, right, down or left
+    #[arg(short, long, default_value = "right", value_parser = parse_direction)]
+    facing: Direction,
+
+    /// Direction to face when reaching an end, any if not set
+    #[arg(short, long = "end-facing", value_parser = parse_direction)]
+    end_facing: Option<Direction>,
+}
+
+impl From<MazeArgs> for Options {
+    fn from(args: MazeArgs) -> Self {
+        Self {
+            costs: Costs {
+                rotation: args.rotation,
+                translation: args.translation,
+            },
+            start_facing: args.facing,
+            end_facing: args.end_facing,
+        }
+    }
+}
+
+fn parse_direction(value: &str) -> Result<Direction, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "up" | "u" | "^" => Ok(Direction::Up),
+        "right" | "r" | ">" => Ok(Direction::Right),
+        "down" | "d" | "v" => Ok(Direction::Down),
+        "left" | "l" | "<" => Ok(Direction::Left),
+        _ => Err(format!("invalid direction: {value}")),
+    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
     match args.command {
-        Some(Command::Part1 { file }) => {
-            println!("Result: {}", part1::run(&read_input_u8!(file)?)?)
+        Some(Command::Part1 { file, maze }) => {
+            println!(
+                "Result: {}",
+                part1::run_with(&read_input_u8!(file)?, &maze.into())?
+            )
         }
         Some(Command::Part2 { file }) => {
             println!("Result: {}", part2::run(&read_input_u8!(file)?)?)
         }
+        Some(Command::Paths { file, maze, k }) => {
+            let options = maze.into();
+            let maze = Maze::parse(&read_input_u8!(file)?)?;
+            let paths = match k {
+                Some(k) => maze.cheapest_paths(&options, k)?,
+                None => maze.best_paths(&options)?.unwrap_or_default(),
+            };
+            if paths.is_empty() {
+                return Err(miette!("no path to end"));
+            }
+            print!("{}", maze.render(&paths));
+            for path in &paths {
+                println!("Cost {}, {path}", path.cost(&options.costs));
+            }
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
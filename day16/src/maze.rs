@@ -0,0 +1,341 @@
+//! Reindeer maze with configurable costs, start facing and end conditions.
+
+use common::position::{Direction, Grid, Position};
+use miette::Result;
+use pathfinding::prelude::astar;
+
+use crate::parse::{parse_maze, Cell};
+
+pub type State = (Position, Direction);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Costs {
+    /// Cost of a 90 degrees rotation
+    pub rotation: usize,
+    /// Cost of a step forward
+    pub translation: usize,
+}
+
+impl Default for Costs {
+    fn default() -> Self {
+        Self {
+            rotation: 1000,
+            translation: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub costs: Costs,
+    /// Direction the reindeer faces on the start tile
+    pub start_facing: Direction,
+    /// Direction the reindeer must face when reaching an end tile, if any
+    pub end_facing: Option<Direction>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            costs: Costs::default(),
+            start_facing: Direction::Right,
+            end_facing: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Maze {
+    pub grid: Grid<Cell>,
+    pub start: Position,
+    /// All the `E` tiles, reaching any of them is enough
+    pub ends: Vec<Position>,
+}
+
+impl Maze {
+    pub fn parse(content: &[u8]) -> Result<Self> {
+        let (grid, start, ends) = parse_maze(content)?;
+        Ok(Self { grid, start, ends })
+    }
+
+    /// Lowest score from the start to one of the ends, `None` if no end can
+    /// be reached
+    pub fn lowest_cost(&self, options: &Options) -> Option<usize> {
+        astar(
+            &(self.start, options.start_facing),
+            |state| self.successors(state, options),
+            |state| self.heuristic(state, options),
+            |state| self.is_end(state, options),
+        )
+        .map(|(_, cost)| cost)
+    }
+
+    pub fn is_end(&self, &(pos, direction): &State, options: &Options) -> bool {
+        self.ends.contains(&pos) && options.end_facing.is_none_or(|facing| facing == direction)
+    }
+
+    /// Moving forward costs a translation, turning left, right or around
+    /// costs one rotation per quarter turn and is immediately followed by a
+    /// translation. On an end tile, the reindeer can also turn on the spot to
+    /// face the required end direction.
+    pub fn successors(&self, &(pos, direction): &State, options: &Options) -> Vec<(State, usize)> {
+        let costs = &options.costs;
+        let mut successors: Vec<_> = Direction::list()
+            .iter()
+            .filter(|&&to| {
+                self.grid
+                    .get(pos + to)
+                    .is_some_and(|cell| cell == Cell::Empty)
+            })
+            .map(|&to| {
+                let cost = quarter_turns(direction, to) * costs.rotation + costs.translation;
+                ((pos + to, to), cost)
+            })
+            .collect();
+        if let Some(facing) = options.end_facing {
+            if facing != direction && self.ends.contains(&pos) {
+                let cost = quarter_turns(direction, facing) * costs.rotation;
+                successors.push(((pos, facing), cost));
+            }
+        }
+        successors
+    }
+
+    /// Lower bound of the cost to the closest end: the Manhattan distance,
+    /// plus the rotations needed to face every direction the reindeer has to
+    /// move in, and the end facing last if required.
+    pub fn heuristic(&self, &(pos, direction): &State, options: &Options) -> usize {
+        self.ends
+            .iter()
+            .map(|&end| {
+                let need_x = match end.x.cmp(&pos.x) {
+                    std::cmp::Ordering::Less => Some(Direction::Left),
+                    std::cmp::Ordering::Equal => None,
+                    std::cmp::Ordering::Greater => Some(Direction::Right),
+                };
+                let need_y = match end.y.cmp(&pos.y) {
+                    std::cmp::Ordering::Less => Some(Direction::Up),
+                    std::cmp::Ordering::Equal => None,
+                    std::cmp::Ordering::Greater => Some(Direction::Down),
+                };
+                // Each rotation is a quarter turn, so facing the needed
+                // directions in the best order is a lower bound of the number
+                // of rotations
+                let turns = |order: [Option<Direction>; 2]| {
+                    order
+                        .into_iter()
+                        .chain([options.end_facing])
+                        .flatten()
+                        .fold((direction, 0), |(from, turns), to| {
+                            (to, turns + quarter_turns(from, to))
+                        })
+                        .1
+                };
+                let rotations = turns([need_x, need_y]).min(turns([need_y, need_x]));
+                let translations = end.x.abs_diff(pos.x) + end.y.abs_diff(pos.y);
+
+                rotations * options.costs.rotation + translations * options.costs.translation
+            })
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// Minimum number of rotations to go from a direction to another
+pub(crate) fn quarter_turns(from: Direction, to: Direction) -> usize {
+    if from == to {
+        0
+    } else if from == to.reverse() {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pathfinding::prelude::dijkstra;
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE_1: &[u8] = br#"
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+"#;
+
+    const INPUT_SAMPLE_2: &[u8] = br#"
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+"#;
+
+    const INPUT_DEAD_END: &[u8] = br#"
+#####
+#S.E#
+#####
+"#;
+
+    const INPUT_ENDS: &[u8] = br#"
+#######
+#E...E#
+#.###.#
+#.....#
+#..S..#
+#######
+"#;
+    // cspell:enable
+
+    fn lowest_cost_dijkstra(maze: &Maze, options: &Options) -> Option<usize> {
+        dijkstra(
+            &(maze.start, options.start_facing),
+            |state| maze.successors(state, options),
+            |state| maze.is_end(state, options),
+        )
+        .map(|(_, cost)| cost)
+    }
+
+    #[test]
+    fn default() {
+        let maze = Maze::parse(&INPUT_SAMPLE_1[1..]).unwrap();
+        assert_eq!(maze.lowest_cost(&Options::default()), Some(7036));
+        let maze = Maze::parse(&INPUT_SAMPLE_2[1..]).unwrap();
+        assert_eq!(maze.lowest_cost(&Options::default()), Some(11048));
+    }
+
+    #[test]
+    fn compare_dijkstra() {
+        for input in [INPUT_SAMPLE_1, INPUT_SAMPLE_2, INPUT_DEAD_END, INPUT_ENDS] {
+            let maze = Maze::parse(&input[1..]).unwrap();
+            for (rotation, translation) in [(1000, 1), (0, 1), (1, 0), (1, 1), (3, 7), (0, 0)] {
+                for &start_facing in Direction::list() {
+                    let end_facings = Direction::list().iter().copied().map(Some);
+                    for end_facing in [None].into_iter().chain(end_facings) {
+                        let options = Options {
+                            costs: Costs {
+                                rotation,
+                                translation,
+                            },
+                            start_facing,
+                            end_facing,
+                        };
+                        assert_eq!(
+                            maze.lowest_cost(&options),
+                            lowest_cost_dijkstra(&maze, &options),
+                            "{options:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn admissible() {
+        let maze = Maze::parse(&INPUT_SAMPLE_1[1..]).unwrap();
+        let options = Options {
+            costs: Costs {
+                rotation: 5,
+                translation: 2,
+            },
+            end_facing: Some(Direction::Up),
+            ..Default::default()
+        };
+        for &end in &maze.ends {
+            assert_eq!(maze.heuristic(&(end, Direction::Up), &options), 0);
+        }
+        let start = (maze.start, options.start_facing);
+        assert!(maze.heuristic(&start, &options) <= maze.lowest_cost(&options).unwrap());
+    }
+
+    #[test]
+    fn multiple_ends() {
+        let maze = Maze::parse(&INPUT_ENDS[1..]).unwrap();
+        assert_eq!(maze.ends, [Position::new(1, 1), Position::new(5, 1)]);
+
+        // Left then up to the closest end
+        let options = Options {
+            start_facing: Direction::Left,
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(1005));
+
+        // Up the right side to the top right end
+        let options = Options {
+            end_facing: Some(Direction::Up),
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(1005));
+
+        // Then turn on the spot on the top right end
+        let options = Options {
+            end_facing: Some(Direction::Left),
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(2005));
+
+        // Walls above the ends, so turn around on the spot to face down
+        let options = Options {
+            end_facing: Some(Direction::Down),
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(3005));
+
+        // Rotations are free, only the steps to the closest end count
+        let options = Options {
+            costs: Costs {
+                rotation: 0,
+                translation: 1,
+            },
+            end_facing: Some(Direction::Right),
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(5));
+    }
+
+    #[test]
+    fn dead_end_start() {
+        let maze = Maze::parse(&INPUT_DEAD_END[1..]).unwrap();
+        // Facing the wall, turn around before going right
+        let options = Options {
+            start_facing: Direction::Left,
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(2002));
+
+        // And turn around again on the end tile
+        let options = Options {
+            start_facing: Direction::Left,
+            end_facing: Some(Direction::Left),
+            ..Default::default()
+        };
+        assert_eq!(maze.lowest_cost(&options), Some(4002));
+    }
+}
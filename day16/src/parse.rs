@@ -19,8 +19,14 @@ impl From<Cell> for &str {
 }
 
 pub fn parse(content: &[u8]) -> Result<(Grid<Cell>, Position, Position), AdventError> {
+    let (grid, start, ends) = parse_maze(content)?;
+    Ok((grid, start, ends.last().copied().unwrap_or_default()))
+}
+
+/// Same as [`parse`], keeping all the end tiles, in reading order
+pub fn parse_maze(content: &[u8]) -> Result<(Grid<Cell>, Position, Vec<Position>), AdventError> {
     let mut start = Default::default();
-    let mut end = Default::default();
+    let mut ends = Vec::new();
     let mut data = Vec::with_capacity(content.len());
     let mut height = 1;
 
@@ -39,7 +45,7 @@ pub fn parse(content: &[u8]) -> Result<(Grid<Cell>, Position, Position), AdventE
                     data.push(Cell::Empty);
                 }
                 b'E' => {
-                    end = Position { x, y };
+                    ends.push(Position { x, y });
                     data.push(Cell::Empty);
                 }
                 _ => panic!("Invalid char at Ln {y}, Col {x}"),
@@ -49,5 +55,5 @@ pub fn parse(content: &[u8]) -> Result<(Grid<Cell>, Position, Position), AdventE
 
     let width = data.len() / height;
     assert_eq!(data.len(), width * height);
-    Ok((Grid::new(data, width, height), start, end))
+    Ok((Grid::new(data, width, height), start, ends))
 }
@@ -0,0 +1,255 @@
+//! Concrete best paths through a [`Maze`], and their rendering.
+
+use std::fmt::Write;
+
+use common::position::{Direction, Position};
+use miette::{miette, Result};
+use pathfinding::prelude::{astar_bag_collect, yen};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    maze::{quarter_turns, Costs, Maze, Options, State},
+    parse::Cell,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Path {
+    /// Positions and directions, from the start to an end
+    pub states: Vec<State>,
+    /// Number of rotations
+    pub turns: usize,
+    /// Number of steps forward
+    pub steps: usize,
+}
+
+impl Path {
+    pub fn new(states: Vec<State>) -> Self {
+        let turns = states
+            .windows(2)
+            .map(|w| quarter_turns(w[0].1, w[1].1))
+            .sum();
+        let steps = states.windows(2).filter(|w| w[0].0 != w[1].0).count();
+        Self {
+            states,
+            turns,
+            steps,
+        }
+    }
+
+    pub fn cost(&self, costs: &Costs) -> usize {
+        self.turns * costs.rotation + self.steps * costs.translation
+    }
+}
+
+impl Maze {
+    /// All the paths with the lowest score, `None` if no end can be reached.
+    /// Fails if all the moves are free, since there would be infinitely many
+    /// paths.
+    pub fn best_paths(&self, options: &Options) -> Result<Option<Vec<Path>>> {
+        check_costs(&options.costs)?;
+        let Some((paths, _)) = astar_bag_collect(
+            &(self.start, options.start_facing),
+            |state| self.successors(state, options),
+            |state| self.heuristic(state, options),
+            |state| self.is_end(state, options),
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(paths.into_iter().map(Path::new).collect()))
+    }
+
+    /// The `k` cheapest distinct paths without loops, by increasing score.
+    /// Fails if all the moves are free.
+    pub fn cheapest_paths(&self, options: &Options, k: usize) -> Result<Vec<Path>> {
+        check_costs(&options.costs)?;
+        Ok(yen(
+            &(self.start, options.start_facing),
+            |state| self.successors(state, options),
+            |state| self.is_end(state, options),
+            k,
+        )
+        .into_iter()
+        .map(|(states, _)| Path::new(states))
+        .collect())
+    }
+
+    /// The maze with the tiles of the paths drawn as the direction of the
+    /// reindeer, or `O` where the paths disagree
+    pub fn render(&self, paths: &[Path]) -> String {
+        let mut tiles = FxHashMap::default();
+        for &(pos, direction) in paths.iter().flat_map(|path| &path.states) {
+            let c = match direction {
+                Direction::Up => '^',
+                Direction::Right => '>',
+                Direction::Down => 'v',
+                Direction::Left => '<',
+            };
+            tiles
+                .entry(pos)
+                .and_modify(|tile| {
+                    if *tile != c {
+                        *tile = 'O'
+                    }
+                })
+                .or_insert(c);
+        }
+
+        let mut out = String::with_capacity((self.grid.width() + 1) * self.grid.height());
+        for y in 0..self.grid.height() {
+            for x in 0..self.grid.width() {
+                let pos = Position { x, y };
+                let c = if pos == self.start {
+                    'S'
+                } else if self.ends.contains(&pos) {
+                    'E'
+                } else if let Some(&c) = tiles.get(&pos) {
+                    c
+                } else if self.grid.get(pos) == Some(Cell::Wall) {
+                    '#'
+                } else {
+                    '.'
+                };
+                out.push(c);
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// With free rotations and translations, walking in circles costs nothing
+/// and the paths can't be enumerated
+fn check_costs(costs: &Costs) -> Result<()> {
+    if costs.rotation == 0 && costs.translation == 0 {
+        return Err(miette!("rotations and translations can't both be free"));
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut line = String::new();
+        for &(pos, _) in &self.states {
+            if !line.is_empty() {
+                line.push_str(" -> ");
+            }
+            write!(line, "({},{})", pos.x, pos.y)?;
+        }
+        write!(f, "{} turns, {} steps: {line}", self.turns, self.steps)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE_1: &[u8] = br#"
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+"#;
+
+    const INPUT_SMALL: &[u8] = br#"
+#####
+#..E#
+#.#.#
+#S..#
+#####
+"#;
+
+    const OUTPUT_SMALL: &str = r#"
+#####
+#^>E#
+#^#^#
+#S>>#
+#####
+"#;
+    // cspell:enable
+
+    #[test]
+    fn best_paths() {
+        let maze = Maze::parse(&INPUT_SAMPLE_1[1..]).unwrap();
+        let options = Options::default();
+        let paths = maze.best_paths(&options).unwrap().unwrap();
+        assert_eq!(paths.len(), 3);
+        for path in &paths {
+            assert_eq!(path.cost(&options.costs), 7036);
+            assert_eq!((path.turns, path.steps), (7, 36));
+            assert_eq!(path.states[0], (maze.start, Direction::Right));
+            assert!(maze.ends.contains(&path.states.last().unwrap().0));
+        }
+
+        let tiles = paths.iter().flat_map(|path| &path.states).map(|s| s.0);
+        assert_eq!(
+            tiles.unique().count() as u64,
+            crate::part2::run(&INPUT_SAMPLE_1[1..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn cheapest_paths() {
+        let maze = Maze::parse(&INPUT_SMALL[1..]).unwrap();
+        let options = Options::default();
+        let paths = maze.cheapest_paths(&options, 3).unwrap();
+        // Up then right, right then up, then the ways with a U-turn
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0].cost(&options.costs), 1004);
+        assert_eq!(paths[1].cost(&options.costs), 2004);
+        assert_eq!(paths[2].cost(&options.costs), 4006);
+
+        let maze = Maze::parse(&INPUT_SAMPLE_1[1..]).unwrap();
+        let paths = maze.cheapest_paths(&options, 5).unwrap();
+        assert_eq!(paths.len(), 5);
+        assert!(paths[..3].iter().all(|p| p.cost(&options.costs) == 7036));
+        assert!(paths
+            .windows(2)
+            .all(|w| w[0].cost(&options.costs) <= w[1].cost(&options.costs)));
+        assert_eq!(paths.iter().unique().count(), 5);
+    }
+
+    #[test]
+    fn render() {
+        let maze = Maze::parse(&INPUT_SMALL[1..]).unwrap();
+        let options = Options {
+            costs: Costs {
+                rotation: 0,
+                translation: 1,
+            },
+            ..Default::default()
+        };
+        let paths = maze.best_paths(&options).unwrap().unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(maze.render(&paths), &OUTPUT_SMALL[1..]);
+    }
+
+    #[test]
+    fn free_moves() {
+        let maze = Maze::parse(&INPUT_SMALL[1..]).unwrap();
+        let options = Options {
+            costs: Costs {
+                rotation: 0,
+                translation: 0,
+            },
+            ..Default::default()
+        };
+        assert!(maze.best_paths(&options).is_err());
+        assert!(maze.cheapest_paths(&options, 3).is_err());
+        // Still fine for the lowest cost
+        assert_eq!(maze.lowest_cost(&options), Some(0));
+    }
+}
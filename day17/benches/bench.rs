@@ -1,5 +1,5 @@
 use common::read_input_u8;
-use day17::{part1, part2};
+use day17::{parse::parse, part1, part2, part2_lookup};
 
 fn main() {
     // Run registered benchmarks.
@@ -26,4 +26,47 @@ mod part2_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part2::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "1_lookup")]
+    fn lookup(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part2_lookup::run(&content).unwrap());
+    }
+}
+
+mod brute_force_bench {
+    use super::*;
+
+    const CANDIDATES: std::ops::Range<usize> = 0..100_000;
+
+    #[divan::bench(name = "0_machine")]
+    fn machine(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| parse(&read_input_u8!(None).unwrap()).unwrap())
+            .bench_values(|machine| {
+                CANDIDATES
+                    .filter(|&a| {
+                        let mut machine = machine.clone();
+                        machine.set_register(day17::machine::Register::A, a);
+                        machine.exec_till_self()
+                    })
+                    .count()
+            });
+    }
+
+    #[divan::bench(name = "1_compiled")]
+    fn compiled(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| parse(&read_input_u8!(None).unwrap()).unwrap())
+            .bench_values(|machine| {
+                let compiled = machine.compile();
+                let registers = machine.registers();
+                CANDIDATES
+                    .filter(|&a| {
+                        compiled.exec_till_self(day17::compile::Registers { a, ..registers })
+                    })
+                    .count()
+            });
+    }
 }
@@ -0,0 +1,340 @@
+//! Disassembler and assembler for the 3-bit computer.
+//!
+//! The text format has one instruction per line, written as its mnemonic
+//! followed by its operand, with combo operands resolved to the register they
+//! read:
+//!
+//! ```text
+//! bst A     # b = a % 8
+//! bxl 1     # b ^= 1
+//! cdv B     # c = a >> b
+//! out B
+//! jnz 0
+//! ```
+//!
+//! Blank lines and `#` comments are ignored, and so are the `NN:` address
+//! prefixes of [`listing`], so that a listing can be assembled again.
+
+use std::fmt::Display;
+
+use common::error::AdventError;
+use miette::{miette, Result};
+use winnow::{
+    ascii::{dec_uint, line_ending, space0, space1, till_line_ending},
+    combinator::{alt, delimited, opt, preceded, separated, trace},
+    error::{StrContext, StrContextValue},
+    prelude::*,
+};
+
+/// Combo operand, resolved to what it reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combo {
+    /// Literal value, in `0..=3`
+    Value(usize),
+    A,
+    B,
+    C,
+}
+
+impl Combo {
+    pub fn decode(operand: usize) -> Result<Self> {
+        match operand {
+            0..=3 => Ok(Self::Value(operand)),
+            4 => Ok(Self::A),
+            5 => Ok(Self::B),
+            6 => Ok(Self::C),
+            _ => Err(miette!("invalid combo operand {operand}")),
+        }
+    }
+
+    pub fn encode(self) -> usize {
+        match self {
+            Self::Value(v) => v,
+            Self::A => 4,
+            Self::B => 5,
+            Self::C => 6,
+        }
+    }
+}
+
+impl Display for Combo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(v) => write!(f, "{v}"),
+            Self::A => write!(f, "A"),
+            Self::B => write!(f, "B"),
+            Self::C => write!(f, "C"),
+        }
+    }
+}
+
+/// A decoded instruction. Literal operands are in `0..=7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// `a = a >> combo`
+    Adv(Combo),
+    /// `b ^= literal`
+    Bxl(usize),
+    /// `b = combo % 8`
+    Bst(Combo),
+    /// `if a != 0 { pc = literal }`
+    Jnz(usize),
+    /// `b ^= c`. The operand is ignored by the machine, but is kept so that
+    /// disassembling then assembling a program gives it back unchanged.
+    Bxc(usize),
+    /// `output combo % 8`
+    Out(Combo),
+    /// `b = a >> combo`
+    Bdv(Combo),
+    /// `c = a >> combo`
+    Cdv(Combo),
+}
+
+impl Instruction {
+    pub fn decode(opcode: usize, operand: usize) -> Result<Self> {
+        if operand > 7 {
+            return Err(miette!("invalid operand {operand}"));
+        }
+        Ok(match opcode {
+            0 => Self::Adv(Combo::decode(operand)?),
+            1 => Self::Bxl(operand),
+            2 => Self::Bst(Combo::decode(operand)?),
+            3 => Self::Jnz(operand),
+            4 => Self::Bxc(operand),
+            5 => Self::Out(Combo::decode(operand)?),
+            6 => Self::Bdv(Combo::decode(operand)?),
+            7 => Self::Cdv(Combo::decode(operand)?),
+            _ => return Err(miette!("invalid instruction {opcode}")),
+        })
+    }
+
+    pub fn encode(self) -> [usize; 2] {
+        match self {
+            Self::Adv(combo) => [0, combo.encode()],
+            Self::Bxl(literal) => [1, literal],
+            Self::Bst(combo) => [2, combo.encode()],
+            Self::Jnz(literal) => [3, literal],
+            Self::Bxc(ignored) => [4, ignored],
+            Self::Out(combo) => [5, combo.encode()],
+            Self::Bdv(combo) => [6, combo.encode()],
+            Self::Cdv(combo) => [7, combo.encode()],
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Adv(combo) => write!(f, "adv {combo}"),
+            Self::Bxl(literal) => write!(f, "bxl {literal}"),
+            Self::Bst(combo) => write!(f, "bst {combo}"),
+            Self::Jnz(literal) => write!(f, "jnz {literal}"),
+            Self::Bxc(0) => write!(f, "bxc"),
+            Self::Bxc(ignored) => write!(f, "bxc {ignored}"),
+            Self::Out(combo) => write!(f, "out {combo}"),
+            Self::Bdv(combo) => write!(f, "bdv {combo}"),
+            Self::Cdv(combo) => write!(f, "cdv {combo}"),
+        }
+    }
+}
+
+/// Decode a program, one instruction per pair of values.
+///
+/// Jumps to odd addresses (which would interleave another set of
+/// instructions) are not supported.
+pub fn disassemble(program: &[usize]) -> Result<Vec<Instruction>> {
+    if !program.len().is_multiple_of(2) {
+        return Err(miette!("program has an odd length ({})", program.len()));
+    }
+    program
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(idx, pair)| {
+            Instruction::decode(pair[0], pair[1])
+                .map_err(|e| e.wrap_err(format!("at address {}", idx * 2)))
+        })
+        .collect()
+}
+
+/// Disassembled program, one instruction per line, prefixed by its address
+pub fn listing(program: &[usize]) -> Result<String> {
+    Ok(disassemble(program)?
+        .iter()
+        .enumerate()
+        .map(|(idx, instruction)| format!("{:>2}: {instruction}\n", idx * 2))
+        .collect())
+}
+
+/// Turn the text form back into a program
+pub fn assemble(source: &[u8]) -> Result<Vec<usize>, AdventError> {
+    let lines: Vec<Option<Instruction>> =
+        trace("assembler", separated(1.., parse_line, line_ending)).parse(source)?;
+
+    Ok(lines
+        .into_iter()
+        .flatten()
+        .flat_map(Instruction::encode)
+        .collect())
+}
+
+fn parse_line(input: &mut &[u8]) -> PResult<Option<Instruction>> {
+    trace(
+        "parse_line",
+        delimited(
+            space0,
+            opt(preceded(opt(parse_address), parse_instruction)),
+            (space0, opt(('#', till_line_ending))),
+        ),
+    )
+    .parse_next(input)
+}
+
+/// Address prefix of a listing line, ignored
+fn parse_address(input: &mut &[u8]) -> PResult<()> {
+    trace(
+        "parse_address",
+        (dec_uint::<_, usize, _>, ':', space0).void(),
+    )
+    .parse_next(input)
+}
+
+fn parse_instruction(input: &mut &[u8]) -> PResult<Instruction> {
+    trace(
+        "parse_instruction",
+        alt((
+            preceded(("adv", space1), parse_combo).map(Instruction::Adv),
+            preceded(("bxl", space1), parse_literal).map(Instruction::Bxl),
+            preceded(("bst", space1), parse_combo).map(Instruction::Bst),
+            preceded(("jnz", space1), parse_literal).map(Instruction::Jnz),
+            preceded("bxc", opt(preceded(space1, parse_literal)))
+                .map(|ignored| Instruction::Bxc(ignored.unwrap_or(0))),
+            preceded(("out", space1), parse_combo).map(Instruction::Out),
+            preceded(("bdv", space1), parse_combo).map(Instruction::Bdv),
+            preceded(("cdv", space1), parse_combo).map(Instruction::Cdv),
+        ))
+        .context(StrContext::Label("instruction")),
+    )
+    .parse_next(input)
+}
+
+fn parse_literal(input: &mut &[u8]) -> PResult<usize> {
+    trace(
+        "parse_literal",
+        dec_uint::<_, usize, _>
+            .verify(|v| *v <= 7)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "a literal in 0..=7",
+            ))),
+    )
+    .parse_next(input)
+}
+
+fn parse_combo(input: &mut &[u8]) -> PResult<Combo> {
+    trace(
+        "parse_combo",
+        alt((
+            "A".value(Combo::A),
+            "B".value(Combo::B),
+            "C".value(Combo::C),
+            dec_uint::<_, usize, _>
+                .verify(|v| *v <= 3)
+                .map(Combo::Value),
+        ))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "a register or a literal in 0..=3",
+        ))),
+    )
+    .parse_next(input)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::machine::Machine;
+
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 4, 6, 0, 3, 1, 4, 5, 5, 3, 0];
+
+    #[test]
+    fn disassemble_program() {
+        assert_eq!(
+            listing(&PROGRAM).unwrap(),
+            " 0: bst A
+ 2: bxl 1
+ 4: cdv B
+ 6: bxc 6
+ 8: adv 3
+10: bxl 4
+12: out B
+14: jnz 0
+"
+        );
+        assert_eq!(
+            disassemble(&[0, 1, 5, 4, 3, 0]).unwrap(),
+            vec![
+                Instruction::Adv(Combo::Value(1)),
+                Instruction::Out(Combo::A),
+                Instruction::Jnz(0)
+            ]
+        );
+
+        assert!(disassemble(&[0, 7]).is_err());
+        assert!(disassemble(&[8, 0]).is_err());
+        assert!(disassemble(&[1, 8]).is_err());
+        assert!(disassemble(&[0, 1, 5]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = disassemble(&PROGRAM)
+            .unwrap()
+            .iter()
+            .map(|instruction| format!("{instruction}\n"))
+            .collect::<String>();
+        assert_eq!(assemble(text.as_bytes()).unwrap(), PROGRAM);
+        assert_eq!(
+            assemble(listing(&PROGRAM).unwrap().as_bytes()).unwrap(),
+            PROGRAM
+        );
+
+        for opcode in 0..8 {
+            for operand in 0..8 {
+                if let Ok(instruction) = Instruction::decode(opcode, operand) {
+                    let text = instruction.to_string();
+                    assert_eq!(assemble(text.as_bytes()).unwrap(), [opcode, operand]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn assemble_and_run() {
+        let program = assemble(
+            br#"
+# Print A in octal, lowest digit first
+out A     # low 3 bits of A
+adv 3
+
+    jnz 0 # until A == 0
+"#,
+        )
+        .unwrap();
+        assert_eq!(program, vec![5, 4, 0, 3, 3, 0]);
+
+        let mut machine = Machine::new(program, 0o1234, 0, 0);
+        machine.exec();
+        assert_eq!(machine.output(), "4,3,2,1");
+    }
+
+    #[test]
+    fn assemble_errors() {
+        assert!(assemble(b"adv 4").is_err());
+        assert!(assemble(b"bxl 8").is_err());
+        assert!(assemble(b"out D").is_err());
+        assert!(assemble(b"nop").is_err());
+        assert!(assemble(b"bst A bst B").is_err());
+        assert!(assemble(b"jnzA").is_err());
+        assert!(assemble(b"4:").is_err());
+        assert!(assemble(b"4 adv 1").is_err());
+    }
+}
@@ -0,0 +1,492 @@
+//! Compilation of programs to fast evaluators.
+//!
+//! [`Compiled`] pre-decodes each instruction into a closure specialized for
+//! its operand, so running a program doesn't go through the instruction and
+//! combo operand `match`es of [`Machine`] anymore, and doesn't allocate.
+//!
+//! [`to_rust_source`] generates the equivalent Rust function, to be pasted
+//! in a crate (like `part1_hardcoded`) for even more speed.
+//!
+//! The divisions are computed as `a / 2^n` (giving 0 for shifts of 64 bits or
+//! more), where the [`Machine`] would overflow.
+
+use std::fmt::Write as _;
+
+use crate::{
+    asm::{Combo, Instruction},
+    machine::{shr, Machine, Register},
+};
+
+/// Registers of a running compiled program
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+/// What to do after an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Next,
+    Jump(usize),
+    Output(usize),
+}
+
+type Op = Box<dyn Fn(&mut Registers) -> Step + Send + Sync>;
+
+/// A program turned into a list of closures, one per address
+pub struct Compiled {
+    program: Vec<usize>,
+    ops: Vec<Op>,
+}
+
+impl Compiled {
+    pub fn new(program: &[usize]) -> Self {
+        // Jumps can target odd addresses, so compile every address
+        let ops = program
+            .windows(2)
+            .map(|pair| compile_op(pair[0], pair[1]))
+            .collect();
+        Self {
+            program: program.to_vec(),
+            ops,
+        }
+    }
+
+    pub fn program(&self) -> &[usize] {
+        &self.program
+    }
+
+    /// Run the program, calling `on_output` for each output value. Stops
+    /// when the program halts or when `on_output` returns `false`.
+    ///
+    /// Returns the final registers.
+    pub fn run<F>(&self, mut registers: Registers, mut on_output: F) -> Registers
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut pc = 0;
+        while let Some(op) = self.ops.get(pc) {
+            match op(&mut registers) {
+                Step::Next => pc += 2,
+                Step::Jump(target) => pc = target,
+                Step::Output(value) => {
+                    if !on_output(value) {
+                        break;
+                    }
+                    pc += 2;
+                }
+            }
+        }
+        registers
+    }
+
+    /// Run the program and collect its output
+    pub fn exec(&self, registers: Registers) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.run(registers, |value| {
+            out.push(value);
+            true
+        });
+        out
+    }
+
+    /// Same as [`Machine::exec_till_self`]: whether the program outputs
+    /// itself, stopping at the first wrong output
+    pub fn exec_till_self(&self, registers: Registers) -> bool {
+        let mut len = 0;
+        let mut matching = true;
+        self.run(registers, |value| {
+            matching = self.program.get(len) == Some(&value);
+            len += 1;
+            matching
+        });
+        matching && len == self.program.len()
+    }
+}
+
+impl Machine {
+    pub fn compile(&self) -> Compiled {
+        Compiled::new(self.program())
+    }
+
+    /// Current registers, to run a compiled version of the program
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.register(Register::A),
+            b: self.register(Register::B),
+            c: self.register(Register::C),
+        }
+    }
+}
+
+/// Resolve a combo operand at compile time. Registers are read through a
+/// function pointer so that each case is a specialized closure.
+fn combo_reader(combo: Combo) -> fn(&Registers) -> usize {
+    match combo {
+        Combo::Value(0) => |_| 0,
+        Combo::Value(1) => |_| 1,
+        Combo::Value(2) => |_| 2,
+        Combo::Value(3) => |_| 3,
+        Combo::Value(v) => unreachable!("invalid combo value {v}"),
+        Combo::A => |r| r.a,
+        Combo::B => |r| r.b,
+        Combo::C => |r| r.c,
+    }
+}
+
+fn compile_op(opcode: usize, operand: usize) -> Op {
+    let Ok(instruction) = Instruction::decode(opcode, operand) else {
+        // Only fail if actually executed, like the machine
+        return Box::new(move |_| panic!("invalid instruction {opcode},{operand}"));
+    };
+    match instruction {
+        Instruction::Adv(Combo::Value(v)) => Box::new(move |r| {
+            r.a >>= v;
+            Step::Next
+        }),
+        Instruction::Adv(combo) => {
+            let read = combo_reader(combo);
+            Box::new(move |r| {
+                r.a = shr(r.a, read(r));
+                Step::Next
+            })
+        }
+        Instruction::Bxl(literal) => Box::new(move |r| {
+            r.b ^= literal;
+            Step::Next
+        }),
+        Instruction::Bst(combo) => {
+            let read = combo_reader(combo);
+            Box::new(move |r| {
+                r.b = read(r) % 8;
+                Step::Next
+            })
+        }
+        Instruction::Jnz(target) => Box::new(move |r| {
+            if r.a != 0 {
+                Step::Jump(target)
+            } else {
+                Step::Next
+            }
+        }),
+        Instruction::Bxc(_) => Box::new(|r| {
+            r.b ^= r.c;
+            Step::Next
+        }),
+        Instruction::Out(combo) => {
+            let read = combo_reader(combo);
+            Box::new(move |r| Step::Output(read(r) % 8))
+        }
+        Instruction::Bdv(Combo::Value(v)) => Box::new(move |r| {
+            r.b = r.a >> v;
+            Step::Next
+        }),
+        Instruction::Bdv(combo) => {
+            let read = combo_reader(combo);
+            Box::new(move |r| {
+                r.b = shr(r.a, read(r));
+                Step::Next
+            })
+        }
+        Instruction::Cdv(Combo::Value(v)) => Box::new(move |r| {
+            r.c = r.a >> v;
+            Step::Next
+        }),
+        Instruction::Cdv(combo) => {
+            let read = combo_reader(combo);
+            Box::new(move |r| {
+                r.c = shr(r.a, read(r));
+                Step::Next
+            })
+        }
+    }
+}
+
+/// Generate a Rust function equivalent to the program:
+///
+/// ```text
+/// pub fn <name>(a: usize, b: usize, c: usize, mut on_output: impl FnMut(usize) -> bool)
+/// ```
+///
+/// with the same behavior as [`Compiled::run`] (without returning the
+/// registers). Only the addresses reachable from the start are generated.
+pub fn to_rust_source(program: &[usize], name: &str) -> String {
+    let reachable = reachable_addresses(program);
+    let read = |combo: Combo| match combo {
+        Combo::Value(v) => v.to_string(),
+        Combo::A => "a".to_string(),
+        Combo::B => "b".to_string(),
+        Combo::C => "c".to_string(),
+    };
+    let shift = |combo: Combo| match combo {
+        Combo::Value(v) => format!("a >> {v}"),
+        _ => format!(
+            "u32::try_from({}).ok().and_then(|s| a.checked_shr(s)).unwrap_or(0)",
+            read(combo)
+        ),
+    };
+
+    let mut source = String::new();
+    let program_text = program
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(source, "/// Generated from program {program_text}").unwrap();
+    writeln!(
+        source,
+        "#[allow(unused_mut, unused_variables, unreachable_code)]"
+    )
+    .unwrap();
+    writeln!(
+        source,
+        "pub fn {name}(mut a: usize, mut b: usize, mut c: usize, mut on_output: impl FnMut(usize) -> bool) {{"
+    )
+    .unwrap();
+    writeln!(source, "    let mut pc = 0;").unwrap();
+    writeln!(source, "    loop {{").unwrap();
+    writeln!(source, "        match pc {{").unwrap();
+    for pc in reachable {
+        let next = pc + 2;
+        let body = match Instruction::decode(program[pc], program[pc + 1]) {
+            Ok(instruction) => {
+                let code = match instruction {
+                    Instruction::Adv(Combo::Value(v)) => format!("a >>= {v};"),
+                    Instruction::Adv(c) => format!("a = {};", shift(c)),
+                    Instruction::Bxl(literal) => format!("b ^= {literal};"),
+                    Instruction::Bst(c) => format!("b = {} % 8;", read(c)),
+                    Instruction::Jnz(target) => {
+                        format!("if a != 0 {{ pc = {target}; continue; }}")
+                    }
+                    Instruction::Bxc(_) => "b ^= c;".to_string(),
+                    Instruction::Out(c) => {
+                        format!("if !on_output({} % 8) {{ return; }}", read(c))
+                    }
+                    Instruction::Bdv(c) => format!("b = {};", shift(c)),
+                    Instruction::Cdv(c) => format!("c = {};", shift(c)),
+                };
+                format!("// {instruction}\n                {code}\n                pc = {next};")
+            }
+            Err(_) => format!(
+                "panic!(\"invalid instruction {},{}\");",
+                program[pc],
+                program[pc + 1]
+            ),
+        };
+        writeln!(
+            source,
+            "            {pc} => {{\n                {body}\n            }}"
+        )
+        .unwrap();
+    }
+    writeln!(source, "            _ => return,").unwrap();
+    writeln!(source, "        }}").unwrap();
+    writeln!(source, "    }}").unwrap();
+    writeln!(source, "}}").unwrap();
+    source
+}
+
+/// Addresses of the instructions that can be executed, in increasing order
+fn reachable_addresses(program: &[usize]) -> Vec<usize> {
+    let mut reachable = vec![false; program.len().saturating_sub(1)];
+    let mut todo = vec![0];
+    while let Some(pc) = todo.pop() {
+        if pc >= reachable.len() || reachable[pc] {
+            continue;
+        }
+        reachable[pc] = true;
+        todo.push(pc + 2);
+        if program[pc] == 3 {
+            todo.push(program[pc + 1]);
+        }
+    }
+    reachable
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| **r)
+        .map(|(pc, _)| pc)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 4, 6, 0, 3, 1, 4, 5, 5, 3, 0];
+
+    #[test]
+    fn same_as_machine() {
+        let programs = [
+            &PROGRAM[..],
+            &[0, 1, 5, 4, 3, 0],
+            &[0, 3, 5, 4, 3, 0],
+            &[5, 0, 5, 1, 5, 4],
+            // Jump to an odd address, running "adv B" then "out A"
+            &[3, 3, 6, 0, 5, 5, 4],
+            &[2, 6, 1, 7, 6, 5, 4, 0, 7, 2, 5, 6, 0, 2, 3, 0],
+        ];
+        for program in programs {
+            let compiled = Compiled::new(program);
+            for a in (0..2000).chain([28066687, 117440, 1 << 40]) {
+                let mut machine = Machine::new(program.to_vec(), a, 3, 5);
+                let out = compiled.exec(machine.registers());
+                machine.exec();
+                assert_eq!(out, machine.out(), "{program:?} with A={a}");
+                assert_eq!(
+                    compiled.run(Registers { a, b: 3, c: 5 }, |_| true),
+                    machine.registers()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn till_self() {
+        let compiled = Compiled::new(&[0, 3, 5, 4, 3, 0]);
+        assert!(compiled.exec_till_self(Registers {
+            a: 117440,
+            ..Default::default()
+        }));
+        assert!(!compiled.exec_till_self(Registers {
+            a: 117441 << 3,
+            ..Default::default()
+        }));
+
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let compiled = machine.compile();
+        let mut machine = Machine::new(PROGRAM.to_vec(), 202972175280682, 0, 0);
+        assert!(compiled.exec_till_self(machine.registers()));
+        assert!(machine.exec_till_self());
+    }
+
+    /// Exact output of `to_rust_source(&PROGRAM, "generated")`
+    const EXPECTED: &str = r#"
+/// Generated from program 2,4,1,1,7,5,4,6,0,3,1,4,5,5,3,0
+#[allow(unused_mut, unused_variables, unreachable_code)]
+pub fn generated(mut a: usize, mut b: usize, mut c: usize, mut on_output: impl FnMut(usize) -> bool) {
+    let mut pc = 0;
+    loop {
+        match pc {
+            0 => {
+                // bst A
+                b = a % 8;
+                pc = 2;
+            }
+            2 => {
+                // bxl 1
+                b ^= 1;
+                pc = 4;
+            }
+            4 => {
+                // cdv B
+                c = u32::try_from(b).ok().and_then(|s| a.checked_shr(s)).unwrap_or(0);
+                pc = 6;
+            }
+            6 => {
+                // bxc 6
+                b ^= c;
+                pc = 8;
+            }
+            8 => {
+                // adv 3
+                a >>= 3;
+                pc = 10;
+            }
+            10 => {
+                // bxl 4
+                b ^= 4;
+                pc = 12;
+            }
+            12 => {
+                // out B
+                if !on_output(b % 8) { return; }
+                pc = 14;
+            }
+            14 => {
+                // jnz 0
+                if a != 0 { pc = 0; continue; }
+                pc = 16;
+            }
+            _ => return,
+        }
+    }
+}
+"#;
+
+    /// The generated source of PROGRAM, pasted
+    #[allow(unused_mut, unused_variables, unreachable_code)]
+    #[rustfmt::skip]
+    pub fn generated(mut a: usize, mut b: usize, mut c: usize, mut on_output: impl FnMut(usize) -> bool) {
+        let mut pc = 0;
+        loop {
+            match pc {
+                0 => {
+                    // bst A
+                    b = a % 8;
+                    pc = 2;
+                }
+                2 => {
+                    // bxl 1
+                    b ^= 1;
+                    pc = 4;
+                }
+                4 => {
+                    // cdv B
+                    c = u32::try_from(b).ok().and_then(|s| a.checked_shr(s)).unwrap_or(0);
+                    pc = 6;
+                }
+                6 => {
+                    // bxc 6
+                    b ^= c;
+                    pc = 8;
+                }
+                8 => {
+                    // adv 3
+                    a >>= 3;
+                    pc = 10;
+                }
+                10 => {
+                    // bxl 4
+                    b ^= 4;
+                    pc = 12;
+                }
+                12 => {
+                    // out B
+                    if !on_output(b % 8) { return; }
+                    pc = 14;
+                }
+                14 => {
+                    // jnz 0
+                    if a != 0 { pc = 0; continue; }
+                    pc = 16;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    #[test]
+    fn rust_source() {
+        let source = to_rust_source(&PROGRAM, "generated");
+        assert_eq!(source, &EXPECTED[1..]);
+
+        let mut out = Vec::new();
+        generated(28066687, 0, 0, |v| {
+            out.push(v);
+            true
+        });
+        assert_eq!(out, [7, 3, 0, 5, 7, 1, 4, 0, 5]);
+
+        // Odd addresses are skipped unless jumped to, and invalid
+        // instructions only panic when executed
+        let source = to_rust_source(&[3, 4, 5, 7, 5, 4], "invalid");
+        assert!(source.contains("2 => {\n                panic!(\"invalid instruction 5,7\");"));
+        assert!(source.contains("4 => {\n                // out A"));
+        assert!(!source.contains("1 => {"));
+        assert!(!source.contains("3 => {"));
+        let source = to_rust_source(&[3, 3, 6, 0, 5, 5, 4], "odd");
+        assert!(source.contains("3 => {\n                // adv B"));
+    }
+}
@@ -0,0 +1,547 @@
+//! Interactive step debugger for the [`Machine`].
+//!
+//! The [`Debugger`] wraps a machine and executes it one instruction at a time
+//! with [`Machine::exec_single`], stopping on breakpoints (on the PC),
+//! watchpoints (on a register change, or on an output that doesn't match the
+//! program like [`Machine::exec_till_self`] checks) and invalid instructions.
+//!
+//! [`run`] is the command-line front-end, see [`HELP`] for the commands.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Write as _},
+    io::{BufRead, Write},
+};
+
+use miette::{miette, IntoDiagnostic, Result};
+
+use crate::{
+    asm::Instruction,
+    machine::{Machine, Register},
+};
+
+const REGISTERS: [Register; 3] = [Register::A, Register::B, Register::C];
+
+pub const HELP: &str = "\
+Commands:
+  s, step [n]         execute the next n instructions (default 1)
+  c, continue         run until a breakpoint, a watchpoint or the end
+  b, break <pc>       add a breakpoint on an address
+  d, delete <pc>      remove a breakpoint
+  w, watch <a|b|c>    stop when a register changes
+  w, watch out        stop when the output stops matching the program
+  unwatch <a|b|c|out> remove a watchpoint
+  set <a|b|c> <value> change a register (0x, 0o and 0b prefixes accepted)
+  r, regs             show the registers
+  l, list             show the program
+  o, out              show the output history
+  reset               restart from the initial registers
+  h, help             show this help
+  q, quit             exit
+An empty line repeats the previous command.";
+
+/// Why the execution stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A single step was executed without anything noticeable
+    Step,
+    /// The PC is past the end of the program
+    Halted,
+    /// The PC reached a breakpoint
+    Breakpoint(usize),
+    /// A watched register changed
+    Watchpoint {
+        register: Register,
+        old: usize,
+        new: usize,
+    },
+    /// The output doesn't match the program anymore
+    OutputMismatch {
+        index: usize,
+        value: usize,
+        expected: Option<usize>,
+    },
+    /// The instruction at the PC can't be decoded, so executing it would
+    /// panic
+    InvalidInstruction(usize),
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Step => write!(f, "stepped"),
+            Self::Halted => write!(f, "halted"),
+            Self::Breakpoint(pc) => write!(f, "breakpoint at {pc}"),
+            Self::Watchpoint { register, old, new } => {
+                write!(f, "register {register:?} changed: {old} -> {new}")
+            }
+            Self::OutputMismatch {
+                index,
+                value,
+                expected: Some(expected),
+            } => write!(f, "output #{index} is {value}, expected {expected}"),
+            Self::OutputMismatch {
+                index,
+                value,
+                expected: None,
+            } => write!(f, "output #{index} is {value}, past the end of the program"),
+            Self::InvalidInstruction(pc) => write!(f, "invalid instruction at {pc}"),
+        }
+    }
+}
+
+/// An output value, and when it was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputEvent {
+    /// Number of instructions executed so far, including the `out`
+    pub step: usize,
+    /// Address of the `out` instruction
+    pub pc: usize,
+    pub value: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    initial: Machine,
+    machine: Machine,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Register>,
+    check_output: bool,
+    steps: usize,
+    history: Vec<OutputEvent>,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Self {
+        Self {
+            initial: machine.clone(),
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            check_output: false,
+            steps: 0,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn history(&self) -> &[OutputEvent] {
+        &self.history
+    }
+
+    /// Returns whether the breakpoint is new
+    pub fn add_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.insert(pc)
+    }
+
+    /// Returns whether the breakpoint existed
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn watch(&mut self, register: Register) {
+        if !self.watchpoints.contains(&register) {
+            self.watchpoints.push(register);
+        }
+    }
+
+    pub fn unwatch(&mut self, register: Register) {
+        self.watchpoints.retain(|r| *r != register);
+    }
+
+    /// Stop as soon as the output diverges from the program
+    pub fn set_check_output(&mut self, check: bool) {
+        self.check_output = check;
+    }
+
+    pub fn set_register(&mut self, register: Register, value: usize) {
+        self.machine.set_register(register, value);
+    }
+
+    /// Restart from the initial machine (or the one given), keeping the
+    /// breakpoints and watchpoints
+    pub fn reset(&mut self, machine: Option<Machine>) {
+        if let Some(machine) = machine {
+            self.initial = machine;
+        }
+        self.machine = self.initial.clone();
+        self.steps = 0;
+        self.history.clear();
+    }
+
+    /// Execute a single instruction
+    pub fn step(&mut self) -> StopReason {
+        self.exec_one().unwrap_or(StopReason::Step)
+    }
+
+    /// Execute instructions until something stops the execution
+    pub fn cont(&mut self) -> StopReason {
+        loop {
+            if let Some(reason) = self.exec_one() {
+                return reason;
+            }
+        }
+    }
+
+    fn exec_one(&mut self) -> Option<StopReason> {
+        let pc = self.machine.pc();
+        let program = self.machine.program();
+        let Some(&[opcode, operand]) = program.get(pc..pc + 2) else {
+            return Some(StopReason::Halted);
+        };
+        if Instruction::decode(opcode, operand).is_err() {
+            return Some(StopReason::InvalidInstruction(pc));
+        }
+
+        let before = REGISTERS.map(|register| self.machine.register(register));
+        let out_len = self.machine.out().len();
+        self.machine.exec_single();
+        self.steps += 1;
+
+        if let Some(&value) = self.machine.out().get(out_len) {
+            self.history.push(OutputEvent {
+                step: self.steps,
+                pc,
+                value,
+            });
+            let expected = self.machine.program().get(out_len).copied();
+            if self.check_output && expected != Some(value) {
+                return Some(StopReason::OutputMismatch {
+                    index: out_len,
+                    value,
+                    expected,
+                });
+            }
+        }
+
+        for (register, old) in REGISTERS.into_iter().zip(before) {
+            let new = self.machine.register(register);
+            if old != new && self.watchpoints.contains(&register) {
+                return Some(StopReason::Watchpoint { register, old, new });
+            }
+        }
+
+        let pc = self.machine.pc();
+        if self.breakpoints.contains(&pc) {
+            return Some(StopReason::Breakpoint(pc));
+        }
+
+        None
+    }
+
+    /// Current registers
+    pub fn registers(&self) -> String {
+        let m = &self.machine;
+        format!(
+            "pc={} a={} (0o{:o}) b={} c={} steps={}",
+            m.pc(),
+            m.register(Register::A),
+            m.register(Register::A),
+            m.register(Register::B),
+            m.register(Register::C),
+            self.steps
+        )
+    }
+
+    /// Program listing, with the PC (`>`) and the breakpoints (`*`)
+    pub fn listing(&self) -> String {
+        let program = self.machine.program();
+        let mut listing = String::new();
+        for (pc, pair) in program.chunks(2).enumerate().map(|(i, p)| (i * 2, p)) {
+            let marker = if pc == self.machine.pc() { '>' } else { ' ' };
+            let breakpoint = if self.breakpoints.contains(&pc) {
+                '*'
+            } else {
+                ' '
+            };
+            let instruction = match pair {
+                &[opcode, operand] => Instruction::decode(opcode, operand)
+                    .map_or_else(|_| format!("?? {opcode},{operand}"), |i| i.to_string()),
+                _ => format!("?? {pair:?}"),
+            };
+            writeln!(listing, "{marker}{breakpoint}{pc:>2}: {instruction}").unwrap();
+        }
+        listing
+    }
+
+    /// Output history, one line per value
+    pub fn output_history(&self) -> String {
+        let mut text = String::new();
+        for (idx, event) in self.history.iter().enumerate() {
+            let expected = self.machine.program().get(idx);
+            let status = match expected {
+                Some(&expected) if expected == event.value => "",
+                _ => " (differs from program)",
+            };
+            writeln!(
+                text,
+                "#{idx:<3} {} (pc={}, step={}){status}",
+                event.value, event.pc, event.step
+            )
+            .unwrap();
+        }
+        write!(text, "output: {}", self.machine.output()).unwrap();
+        text
+    }
+
+    /// Run one command line. Returns the text to display, or `None` to quit.
+    pub fn command(&mut self, line: &str) -> Result<Option<String>> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Some(String::new()));
+        };
+        let args = words.collect::<Vec<_>>();
+
+        let text = match (command, args.as_slice()) {
+            ("s" | "step", []) => {
+                let reason = self.step();
+                self.stop_message(reason)
+            }
+            ("s" | "step", [count]) => {
+                let count = parse_value(count)?;
+                let mut reason = StopReason::Step;
+                for _ in 0..count {
+                    reason = self.step();
+                    if reason != StopReason::Step {
+                        break;
+                    }
+                }
+                self.stop_message(reason)
+            }
+            ("c" | "continue", []) => {
+                let reason = self.cont();
+                self.stop_message(reason)
+            }
+            ("b" | "break", [pc]) => {
+                let pc = parse_value(pc)?;
+                self.add_breakpoint(pc);
+                format!("breakpoint at {pc}")
+            }
+            ("d" | "delete", [pc]) => {
+                let pc = parse_value(pc)?;
+                if !self.remove_breakpoint(pc) {
+                    return Err(miette!("no breakpoint at {pc}"));
+                }
+                format!("breakpoint at {pc} removed")
+            }
+            ("w" | "watch", ["out"]) => {
+                self.set_check_output(true);
+                "watching the output".to_string()
+            }
+            ("w" | "watch", [register]) => {
+                let register = parse_register(register)?;
+                self.watch(register);
+                format!("watching {register:?}")
+            }
+            ("unwatch", ["out"]) => {
+                self.set_check_output(false);
+                "not watching the output".to_string()
+            }
+            ("unwatch", [register]) => {
+                let register = parse_register(register)?;
+                self.unwatch(register);
+                format!("not watching {register:?}")
+            }
+            ("set", [register, value]) => {
+                self.set_register(parse_register(register)?, parse_value(value)?);
+                self.registers()
+            }
+            ("r" | "regs", []) => self.registers(),
+            ("l" | "list", []) => self.listing(),
+            ("o" | "out", []) => self.output_history(),
+            ("reset", []) => {
+                self.reset(None);
+                self.registers()
+            }
+            ("h" | "help", []) => HELP.to_string(),
+            ("q" | "quit", []) => return Ok(None),
+            _ => return Err(miette!("invalid command '{}', try 'help'", line.trim())),
+        };
+        Ok(Some(text))
+    }
+
+    fn stop_message(&self, reason: StopReason) -> String {
+        let pc = self.machine.pc();
+        let next = self
+            .machine
+            .program()
+            .get(pc..pc + 2)
+            .and_then(|pair| Instruction::decode(pair[0], pair[1]).ok())
+            .map(|instruction| format!("\nnext: {pc:>2}: {instruction}"))
+            .unwrap_or_default();
+        format!("{reason}\n{}{next}", self.registers())
+    }
+}
+
+fn parse_register(name: &str) -> Result<Register> {
+    match name {
+        "a" | "A" => Ok(Register::A),
+        "b" | "B" => Ok(Register::B),
+        "c" | "C" => Ok(Register::C),
+        _ => Err(miette!("invalid register '{name}'")),
+    }
+}
+
+fn parse_value(value: &str) -> Result<usize> {
+    let (digits, radix) = match value.get(..2) {
+        Some("0x") => (&value[2..], 16),
+        Some("0o") => (&value[2..], 8),
+        Some("0b") => (&value[2..], 2),
+        _ => (value, 10),
+    };
+    usize::from_str_radix(digits, radix).into_diagnostic()
+}
+
+/// Interactive loop, reading commands from `input`
+pub fn run(machine: Machine, mut input: impl BufRead, mut output: impl Write) -> Result<()> {
+    let mut debugger = Debugger::new(machine);
+    writeln!(output, "{}", debugger.listing()).into_diagnostic()?;
+    writeln!(output, "{}", debugger.registers()).into_diagnostic()?;
+
+    let mut last_command = String::new();
+    let mut line = String::new();
+    loop {
+        write!(output, "(day17) ").into_diagnostic()?;
+        output.flush().into_diagnostic()?;
+
+        line.clear();
+        if input.read_line(&mut line).into_diagnostic()? == 0 {
+            // End of input
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            line.clone_from(&last_command);
+        } else {
+            last_command.clone_from(&line);
+        }
+
+        match debugger.command(&line) {
+            Ok(Some(text)) if text.is_empty() => {}
+            Ok(Some(text)) => writeln!(output, "{text}").into_diagnostic()?,
+            Ok(None) => return Ok(()),
+            Err(e) => writeln!(output, "error: {e}").into_diagnostic()?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 4, 6, 0, 3, 1, 4, 5, 5, 3, 0];
+
+    #[test]
+    fn step_and_breakpoint() {
+        let mut debugger = Debugger::new(Machine::new(vec![0, 1, 5, 4, 3, 0], 729, 0, 0));
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.machine().register(Register::A), 364);
+
+        debugger.add_breakpoint(4);
+        assert_eq!(debugger.cont(), StopReason::Breakpoint(4));
+        assert_eq!(debugger.machine().output(), "4");
+        assert_eq!(debugger.cont(), StopReason::Breakpoint(4));
+        assert_eq!(debugger.machine().output(), "4,6");
+
+        debugger.remove_breakpoint(4);
+        assert_eq!(debugger.cont(), StopReason::Halted);
+        assert_eq!(debugger.machine().output(), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(debugger.history().len(), 10);
+        assert_eq!(
+            debugger.history()[1],
+            OutputEvent {
+                step: 5,
+                pc: 2,
+                value: 6
+            }
+        );
+        assert_eq!(debugger.cont(), StopReason::Halted);
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut debugger = Debugger::new(Machine::new(PROGRAM.to_vec(), 28066687, 0, 0));
+        debugger.watch(Register::C);
+        assert_eq!(
+            debugger.cont(),
+            StopReason::Watchpoint {
+                register: Register::C,
+                old: 0,
+                new: 28066687 >> 6
+            }
+        );
+        assert_eq!(debugger.machine().pc(), 6);
+        debugger.unwatch(Register::C);
+
+        // Part 1 value is not a quine: stops on the first output
+        debugger.set_check_output(true);
+        assert_eq!(
+            debugger.cont(),
+            StopReason::OutputMismatch {
+                index: 0,
+                value: 7,
+                expected: Some(2)
+            }
+        );
+
+        debugger.reset(None);
+        debugger.set_register(Register::A, 0);
+        debugger.set_check_output(false);
+        assert_eq!(debugger.cont(), StopReason::Halted);
+        assert_eq!(debugger.machine().output(), "5");
+    }
+
+    #[test]
+    fn invalid_instruction() {
+        let mut debugger = Debugger::new(Machine::new(vec![5, 7], 0, 0, 0));
+        assert_eq!(debugger.cont(), StopReason::InvalidInstruction(0));
+        assert_eq!(debugger.steps(), 0);
+    }
+
+    #[test]
+    fn large_shift() {
+        // adv B, bdv C, cdv B with shifts past the register width
+        let mut debugger = Debugger::new(Machine::new(vec![0, 5, 6, 6, 7, 5], 1 << 40, 0, 0));
+        debugger.set_register(Register::B, 64);
+        debugger.set_register(Register::C, usize::MAX);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.machine().register(Register::A), 0);
+        debugger.set_register(Register::A, 1 << 40);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.machine().register(Register::B), 0);
+        debugger.set_register(Register::B, 1 << 33);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.machine().register(Register::C), 0);
+    }
+
+    #[test]
+    fn commands() {
+        let input = b"b 12\nl\nc\n\nset a 0o17\nw out\nc\no\nbad\nq\nstep\n";
+        let mut output = Vec::new();
+        run(
+            Machine::new(PROGRAM.to_vec(), 28066687, 0, 0),
+            &input[..],
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(">  0: bst A\n   2: bxl 1\n"));
+        assert!(output.contains(" *12: out B\n"));
+        assert!(output.contains("breakpoint at 12\npc=12 a=3508335 (0o15304157) b=438543"));
+        // The empty line repeats "c"
+        assert!(output.contains("breakpoint at 12\npc=12 a=438541 "));
+        assert!(output.contains("pc=12 a=15 (0o17) b=54819 c=54817 steps=14"));
+        assert!(output.contains("output #1 is 3, expected 4"));
+        assert!(output.contains("#1   3 (pc=12, step=15) (differs from program)\noutput: 7,3"));
+        assert!(output.contains("error: invalid command 'bad', try 'help'"));
+        // Nothing is executed after "q"
+        assert!(output.ends_with("(day17) "));
+    }
+}
@@ -1,6 +1,12 @@
+pub mod asm;
+pub mod compile;
+pub mod debugger;
 pub mod machine;
 pub mod parse;
 pub mod part1;
 pub mod part1_hardcoded;
 pub mod part2;
 pub mod part2_brute_force;
+pub mod part2_lookup;
+pub mod quine;
+pub mod search;
@@ -1,6 +1,18 @@
 use std::fmt::Debug;
 
-use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use miette::{miette, Result};
+
+use crate::{
+    asm::{self, Instruction},
+    search::{BruteForce, Outcome},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
 
 #[derive(Clone)]
 pub struct Machine {
@@ -28,6 +40,35 @@ impl Machine {
         &self.instructions
     }
 
+    pub fn register(&self, register: Register) -> usize {
+        match register {
+            Register::A => self.reg_a,
+            Register::B => self.reg_b,
+            Register::C => self.reg_c,
+        }
+    }
+
+    pub fn set_register(&mut self, register: Register, value: usize) {
+        match register {
+            Register::A => self.reg_a = value,
+            Register::B => self.reg_b = value,
+            Register::C => self.reg_c = value,
+        }
+    }
+
+    pub fn pc(&self) -> usize {
+        self.reg_pc
+    }
+
+    /// Raw output values
+    pub fn out(&self) -> &[usize] {
+        &self.out
+    }
+
+    pub fn disassemble(&self) -> Result<Vec<Instruction>> {
+        asm::disassemble(&self.instructions)
+    }
+
     pub fn output(&self) -> String {
         self.out
             .iter()
@@ -40,19 +81,15 @@ impl Machine {
         while self.exec_single() {}
     }
 
-    pub fn fix_exec(&mut self) -> Option<usize> {
-        (0..usize::MAX)
-            .into_par_iter()
-            .with_min_len(1_000_000)
-            .by_exponential_blocks()
-            .find_first(|&v| {
-                let mut machine = self.clone();
-                // Reset the registers and output
-                machine.reg_a = v;
-                // println!("Testing {self:?}");
-
-                machine.exec_till_self()
-            })
+    /// Lowest value of register A making the program output itself, by brute
+    /// force on a compiled version of the program. See [`BruteForce`] for
+    /// progress reports, timeout and resuming.
+    pub fn fix_exec(&mut self) -> Result<Option<usize>> {
+        match BruteForce::default().run(self)? {
+            Outcome::Found(a) => Ok(Some(a)),
+            Outcome::Exhausted => Ok(None),
+            Outcome::Stopped { next } => Err(miette!("search stopped at {next}")),
+        }
     }
 
     pub fn exec_till_self(&mut self) -> bool {
@@ -63,17 +100,10 @@ impl Machine {
                 // new output, verify we are still valid
                 if out_len > self.instructions.len() {
                     // Too much output
-                    // println!("too much output");
                     return false;
                 }
                 if self.out[out_len - 1] != self.instructions[out_len - 1] {
                     // No match
-                    // println!(
-                    //     "wrong output @{}: {} (expected {})",
-                    //     out_len - 1,
-                    //     self.out[out_len - 1],
-                    //     self.instructions[out_len - 1]
-                    // );
                     return false;
                 }
             }
@@ -102,7 +132,6 @@ impl Machine {
 
     fn next_instruction(&self) -> Option<&[usize; 2]> {
         if self.reg_pc + 2 > self.instructions.len() {
-            // println!("invalid PC {}", self.reg_pc);
             None
         } else {
             self.instructions[self.reg_pc..self.reg_pc + 2]
@@ -113,7 +142,7 @@ impl Machine {
 
     fn exec_adv(&mut self, operand: usize) {
         // "... / 2^n" is the same a ">>n"
-        self.reg_a >>= self.combo_operand(operand);
+        self.reg_a = shr(self.reg_a, self.combo_operand(operand));
         self.reg_pc += 2;
     }
 
@@ -147,13 +176,13 @@ impl Machine {
 
     fn exec_bdv(&mut self, operand: usize) {
         // "... / 2^n" is the same a ">>n"
-        self.reg_b = self.reg_a >> self.combo_operand(operand);
+        self.reg_b = shr(self.reg_a, self.combo_operand(operand));
         self.reg_pc += 2;
     }
 
     fn exec_cdv(&mut self, operand: usize) {
         // "... / 2^n" is the same a ">>n"
-        self.reg_c = self.reg_a >> self.combo_operand(operand);
+        self.reg_c = shr(self.reg_a, self.combo_operand(operand));
         self.reg_pc += 2;
     }
 
@@ -187,6 +216,14 @@ impl Debug for Machine {
     }
 }
 
+/// `value >> shift`, 0 instead of overflowing when B or C hold a large shift
+pub(crate) fn shr(value: usize, shift: usize) -> usize {
+    u32::try_from(shift)
+        .ok()
+        .and_then(|shift| value.checked_shr(shift))
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
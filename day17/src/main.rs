@@ -1,8 +1,13 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
-use day17::{part1, part1_hardcoded, part2, part2_brute_force};
-use miette::Result;
-use std::path::PathBuf;
+use day17::{
+    asm, compile, debugger,
+    parse::{parse, parse_values},
+    part1, part1_hardcoded, part2, part2_brute_force, part2_lookup, quine,
+    search::{BruteForce, Outcome},
+};
+use miette::{IntoDiagnostic, Result};
+use std::{path::PathBuf, time::Duration};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -24,6 +29,66 @@ enum Command {
 
     /// Part 2
     Part2BruteForce,
+
+    /// Part 2
+    Part2Lookup { file: Option<PathBuf> },
+
+    /// Print the program of an input file in mnemonic form
+    Disasm { file: Option<PathBuf> },
+
+    /// Turn a program in mnemonic form into its list of values
+    Asm { file: PathBuf },
+
+    /// Step through the program of an input file
+    Debug { file: Option<PathBuf> },
+
+    /// Generate the Rust source of the program of an input file
+    Compile {
+        file: Option<PathBuf>,
+
+        /// File to write the source to (stdout by default)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Brute force the value of register A making the program output itself
+    Search {
+        file: Option<PathBuf>,
+
+        /// First value to check
+        #[arg(long, default_value_t = 0)]
+        start: usize,
+
+        /// Last value to check (excluded)
+        #[arg(long, default_value_t = usize::MAX)]
+        end: usize,
+
+        /// Number of values checked between progress reports
+        #[arg(long = "chunk-size", default_value_t = 1 << 24)]
+        chunk_size: usize,
+
+        /// Stop after this number of seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// File to save the progress to, and resume from
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+    },
+
+    /// Find the values of register A producing an output (the program itself
+    /// by default)
+    Solve {
+        file: Option<PathBuf>,
+
+        /// Comma-separated output to produce
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Maximum number of values to print
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+    },
 }
 
 fn main() -> Result<()> {
@@ -42,6 +107,78 @@ fn main() -> Result<()> {
         Some(Command::Part2BruteForce) => {
             part2_brute_force::run();
         }
+        Some(Command::Part2Lookup { file }) => {
+            println!("Result: {}", part2_lookup::run(&read_input_u8!(file)?)?);
+        }
+        Some(Command::Disasm { file }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            print!("{}", asm::listing(machine.program())?);
+        }
+        Some(Command::Asm { file }) => {
+            let program = asm::assemble(&read_input_u8!(Some(file))?)?;
+            let program = program
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            println!("Program: {program}");
+        }
+        Some(Command::Debug { file }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            debugger::run(machine, std::io::stdin().lock(), std::io::stdout())?;
+        }
+        Some(Command::Compile { file, output }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            let source = compile::to_rust_source(machine.program(), "run_program");
+            match output {
+                Some(output) => std::fs::write(output, source).into_diagnostic()?,
+                None => print!("{source}"),
+            }
+        }
+        Some(Command::Search {
+            file,
+            start,
+            end,
+            chunk_size,
+            timeout,
+            checkpoint,
+        }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            let outcome = BruteForce {
+                start,
+                end,
+                chunk_size,
+                timeout: timeout.map(Duration::from_secs),
+                cancel: None,
+                checkpoint,
+                on_progress: Some(Box::new(|progress| eprintln!("{progress}"))),
+            }
+            .run(&machine)?;
+            match outcome {
+                Outcome::Found(a) => println!("Result: {a}"),
+                Outcome::Exhausted => println!("No solution in the range"),
+                Outcome::Stopped { next } => println!("Stopped, resume from {next}"),
+            }
+        }
+        Some(Command::Solve {
+            file,
+            target,
+            count,
+        }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            let target = match target {
+                Some(target) => parse_values(target.as_bytes())?,
+                None => machine.program().clone(),
+            };
+            let mut found = 0;
+            for a in quine::find_register_a(&machine, &target)?.take(count) {
+                println!("{a}");
+                found += 1;
+            }
+            if found == 0 {
+                println!("No solution found");
+            }
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
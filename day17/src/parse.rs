@@ -19,6 +19,18 @@ pub fn parse(content: &[u8]) -> Result<Machine, AdventError> {
     .parse(content)?)
 }
 
+/// Comma-separated list of values, e.g. a target output
+pub fn parse_values(content: &[u8]) -> Result<Vec<usize>, AdventError> {
+    Ok(trace(
+        "parse_values",
+        terminated(
+            separated(1.., dec_uint::<_, usize, _>, ','),
+            opt(line_ending),
+        ),
+    )
+    .parse(content)?)
+}
+
 fn parse_registers(input: &mut &[u8]) -> PResult<(usize, usize, usize)> {
     trace(
         "parse_registers",
@@ -0,0 +1,317 @@
+//! Generic solver for the values of register A making a program output a
+//! given sequence, e.g. its own code.
+//!
+//! It works on the family of programs made of a single loop:
+//! - the last instruction is `jnz 0`, and there is no other jump,
+//! - A is only modified by a single `adv` with a literal operand (the shift),
+//! - the loop body contains a single `out`,
+//! - B and C are always written before being read in the loop body, so each
+//!   iteration only depends on the value of A at its start.
+//!
+//! Iteration `i` then runs with `A >> (shift * i)`, so the last output only
+//! depends on the highest bits of A. The solver searches A backwards, `shift`
+//! bits at a time, starting from the last output, and yields the solutions
+//! in increasing order. The candidates are checked
+//! with the real [`Machine`], so nothing about the computation of the output
+//! needs to be known.
+
+use miette::{miette, Result};
+
+use crate::{
+    asm::{self, Combo, Instruction},
+    machine::{Machine, Register},
+};
+
+/// Properties of a program of the supported family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopProgram {
+    /// Number of bits A is shifted by on each iteration
+    pub shift: usize,
+}
+
+/// Check that the program belongs to the supported family (see the module
+/// documentation), with an error explaining why it doesn't.
+pub fn analyze(program: &[usize]) -> Result<LoopProgram> {
+    let instructions = asm::disassemble(program)?;
+
+    let Some((last, body)) = instructions.split_last() else {
+        return Err(miette!("empty program"));
+    };
+    if *last != Instruction::Jnz(0) {
+        return Err(miette!(
+            "the program must end with 'jnz 0' (found '{last}')"
+        ));
+    }
+
+    let mut shift = None;
+    let mut outputs = 0;
+    // Whether B and C have been written in the current iteration
+    let (mut b_set, mut c_set) = (false, false);
+    for (idx, instruction) in body.iter().enumerate() {
+        let pc = idx * 2;
+        let reads = |combo: Combo, b_set: bool, c_set: bool| match combo {
+            Combo::B if !b_set => Err(miette!(
+                "'{instruction}' at {pc} reads B from the previous iteration"
+            )),
+            Combo::C if !c_set => Err(miette!(
+                "'{instruction}' at {pc} reads C from the previous iteration"
+            )),
+            _ => Ok(()),
+        };
+
+        match *instruction {
+            Instruction::Adv(Combo::Value(value)) if value > 0 => {
+                if shift.replace(value).is_some() {
+                    return Err(miette!("A is shifted more than once (at {pc})"));
+                }
+            }
+            Instruction::Adv(combo) => {
+                return Err(miette!(
+                    "'adv {combo}' at {pc}: A must be shifted by a non-zero constant"
+                ))
+            }
+            Instruction::Bxl(_) => {
+                reads(Combo::B, b_set, c_set)?;
+            }
+            Instruction::Bst(combo) => {
+                reads(combo, b_set, c_set)?;
+                b_set = true;
+            }
+            Instruction::Jnz(_) => {
+                return Err(miette!(
+                    "'{instruction}' at {pc}: only the last instruction can jump"
+                ))
+            }
+            Instruction::Bxc(_) => {
+                reads(Combo::B, b_set, c_set)?;
+                reads(Combo::C, b_set, c_set)?;
+            }
+            Instruction::Out(combo) => {
+                reads(combo, b_set, c_set)?;
+                outputs += 1;
+            }
+            Instruction::Bdv(combo) => {
+                reads(combo, b_set, c_set)?;
+                b_set = true;
+            }
+            Instruction::Cdv(combo) => {
+                reads(combo, b_set, c_set)?;
+                c_set = true;
+            }
+        }
+    }
+
+    let shift = shift.ok_or_else(|| miette!("A is never shifted, the program never ends"))?;
+    if outputs != 1 {
+        return Err(miette!(
+            "the loop must output exactly one value (found {outputs} 'out')"
+        ));
+    }
+
+    Ok(LoopProgram { shift })
+}
+
+/// Find the lowest value of register A making the program output itself.
+///
+/// Returns an error if the program is outside of the supported family, and
+/// `None` if there is no solution.
+pub fn solve(machine: &Machine) -> Result<Option<usize>> {
+    Ok(find_register_a(machine, machine.program())?.next())
+}
+
+/// All the values of register A making the program output `target`, in
+/// increasing order.
+///
+/// Returns an error if the program is outside of the supported family.
+pub fn find_register_a(machine: &Machine, target: &[usize]) -> Result<Solutions> {
+    let LoopProgram { shift } = analyze(machine.program())?;
+
+    let machine = Machine::new(
+        machine.program().to_vec(),
+        0,
+        machine.register(Register::B),
+        machine.register(Register::C),
+    );
+    // The loop runs at least once, so there is always some output
+    let stack = match target.len() {
+        0 => vec![],
+        len => vec![Frame {
+            len: len - 1,
+            prefix: 0,
+            bits: 0,
+        }],
+    };
+
+    Ok(Solutions {
+        machine,
+        target: target.to_vec(),
+        shift,
+        stack,
+    })
+}
+
+/// Iterator over the solutions, see [`find_register_a`]
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    machine: Machine,
+    target: Vec<usize>,
+    shift: usize,
+    stack: Vec<Frame>,
+}
+
+/// Search state: `prefix` already outputs `target[len + 1..]`, and we are
+/// trying to extend it with `bits` to output `target[len..]`
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    len: usize,
+    prefix: usize,
+    bits: usize,
+}
+
+impl Iterator for Solutions {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some(frame) = self.stack.last_mut() {
+            let Frame { len, prefix, bits } = *frame;
+            if bits == 1 << self.shift {
+                self.stack.pop();
+                continue;
+            }
+            frame.bits += 1;
+
+            let Some(a) = prefix.checked_mul(1 << self.shift) else {
+                // Any larger A overflows too
+                self.stack.pop();
+                continue;
+            };
+            let a = a | bits;
+            if a == 0 && len > 0 {
+                // A leading zero would produce one output less
+                continue;
+            }
+
+            let mut candidate = self.machine.clone();
+            candidate.reg_a = a;
+            candidate.exec();
+            if candidate.out() != &self.target[len..] {
+                continue;
+            }
+
+            if len == 0 {
+                return Some(a);
+            }
+            self.stack.push(Frame {
+                len: len - 1,
+                prefix: a,
+                bits: 0,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 4, 6, 0, 3, 1, 4, 5, 5, 3, 0];
+
+    fn check(program: &[usize], expected: usize) {
+        let machine = Machine::new(program.to_vec(), 2024, 0, 0);
+        assert_eq!(solve(&machine).unwrap(), Some(expected));
+
+        let mut machine = Machine::new(program.to_vec(), expected, 0, 0);
+        machine.exec();
+        assert_eq!(machine.out(), program);
+    }
+
+    #[test]
+    fn sample() {
+        // adv 3, out A, jnz 0
+        check(&[0, 3, 5, 4, 3, 0], 117440);
+    }
+
+    #[test]
+    fn programs() {
+        check(&PROGRAM, 202972175280682);
+        // Same program with other constants and some reordering
+        let program =
+            asm::assemble(b"bst A\nbxl 5\ncdv B\nadv 3\nbxc\nbxl 6\nout B\njnz 0").unwrap();
+        let machine = Machine::new(program.clone(), 0, 0, 0);
+        let a = solve(&machine).unwrap().unwrap();
+        let mut machine = Machine::new(program.clone(), a, 0, 0);
+        machine.exec();
+        assert_eq!(machine.out(), program);
+    }
+
+    #[test]
+    fn no_solution() {
+        // Always outputs 0
+        let machine = Machine::new(vec![0, 3, 5, 0, 3, 0], 0, 0, 0);
+        assert_eq!(solve(&machine).unwrap(), None);
+    }
+
+    #[test]
+    fn outside_family() {
+        let analyze = |text: &[u8]| analyze(&asm::assemble(text).unwrap());
+
+        assert_eq!(analyze(b"adv 1\nout A\njnz 0").unwrap().shift, 1);
+        // Doesn't end with 'jnz 0'
+        assert!(analyze(b"adv 3\nout A").is_err());
+        assert!(analyze(b"adv 3\nout A\njnz 2").is_err());
+        // Other jump
+        assert!(analyze(b"jnz 4\nadv 3\nout A\njnz 0").is_err());
+        // Shift not constant, or several shifts
+        assert!(analyze(b"bst A\nadv B\nout A\njnz 0").is_err());
+        assert!(analyze(b"adv 0\nout A\njnz 0").is_err());
+        assert!(analyze(b"adv 1\nadv 2\nout A\njnz 0").is_err());
+        assert!(analyze(b"out A\njnz 0").is_err());
+        // Output count
+        assert!(analyze(b"adv 3\njnz 0").is_err());
+        assert!(analyze(b"adv 3\nout A\nout A\njnz 0").is_err());
+        // State carried between iterations
+        assert!(analyze(b"bxl 1\nadv 3\nout B\njnz 0").is_err());
+        assert!(analyze(b"bst A\nbxc\nadv 3\nout B\njnz 0").is_err());
+        assert!(analyze(b"adv 3\nout C\ncdv 1\njnz 0").is_err());
+    }
+
+    fn brute_force(target: &[usize]) -> Vec<usize> {
+        let start = match target.len() {
+            1 => 0,
+            len => 1 << (3 * (len - 1)),
+        };
+        (start..1 << (3 * target.len()))
+            .filter(|&a| {
+                let mut machine = Machine::new(PROGRAM.to_vec(), a, 0, 0);
+                machine.exec();
+                machine.out() == target
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_all() {
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let solutions = find_register_a(&machine, &[7, 3, 0, 5, 7, 1, 4, 0, 5])
+            .unwrap()
+            .collect::<Vec<_>>();
+        // Part 1 value, and some of the ones from `part1_hardcoded`
+        for a in [28066687, 28057973, 28058485, 28066165] {
+            assert!(solutions.contains(&a));
+        }
+        assert_eq!(solutions[0], 28057973);
+        assert!(solutions.windows(2).all(|w| w[0] < w[1]));
+
+        for target in [&[4][..], &[5, 7], &[1, 4, 0, 5], &[7, 1, 4, 0, 5]] {
+            assert_eq!(
+                find_register_a(&machine, target)
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                brute_force(target),
+                "{target:?}"
+            );
+        }
+        assert_eq!(find_register_a(&machine, &[]).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,420 @@
+//! Resumable brute force search of the register A value making a program
+//! output itself.
+//!
+//! The range is searched in chunks, each chunk in parallel with the
+//! [compiled](crate::compile) program. Between chunks the search reports its
+//! progress, checks for cancellation or timeout, and saves the next value to
+//! check in a checkpoint file so that an interrupted search can be resumed.
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use miette::{miette, IntoDiagnostic, Result};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::{compile::Registers, machine::Machine};
+
+/// Search state reported after each chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Start of the whole range (after resuming from a checkpoint)
+    pub start: usize,
+    /// Next value to check
+    pub next: usize,
+    /// End (excluded) of the whole range
+    pub end: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Number of candidates checked since the start of this run
+    pub fn checked(&self) -> usize {
+        self.next - self.start
+    }
+
+    pub fn candidates_per_sec(&self) -> f64 {
+        self.checked() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "searched {}..{} (end {}), {:.0} candidates/s, {:.1?}",
+            self.start,
+            self.next,
+            self.end,
+            self.candidates_per_sec(),
+            self.elapsed
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Lowest value in the range making the program output itself
+    Found(usize),
+    /// No value in the range works
+    Exhausted,
+    /// Timeout or cancellation, `next` is the first value not checked yet
+    Stopped { next: usize },
+}
+
+pub type ProgressCallback<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+/// Brute force search options
+pub struct BruteForce<'a> {
+    /// Range to search, `start` is replaced by the checkpoint if it exists
+    pub start: usize,
+    pub end: usize,
+    /// Number of candidates checked between progress reports
+    pub chunk_size: usize,
+    /// Stop after this duration (checked between chunks)
+    pub timeout: Option<Duration>,
+    /// Stop when set to `true` (checked between chunks)
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// File where the searched range is saved, and resumed from
+    pub checkpoint: Option<PathBuf>,
+    /// Called after each chunk
+    pub on_progress: Option<ProgressCallback<'a>>,
+}
+
+impl Default for BruteForce<'_> {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: usize::MAX,
+            chunk_size: 1 << 24,
+            timeout: None,
+            cancel: None,
+            checkpoint: None,
+            on_progress: None,
+        }
+    }
+}
+
+impl BruteForce<'_> {
+    pub fn run(&mut self, machine: &Machine) -> Result<Outcome> {
+        let compiled = machine.compile();
+        let registers = machine.registers();
+
+        let mut next = self.start;
+        if let Some(path) = &self.checkpoint {
+            if let Some(checkpoint) = Checkpoint::load(path)? {
+                if checkpoint.program != machine.program().as_slice() {
+                    return Err(miette!(
+                        "checkpoint '{}' is for another program",
+                        path.display()
+                    ));
+                }
+                if (checkpoint.reg_b, checkpoint.reg_c) != (registers.b, registers.c) {
+                    return Err(miette!(
+                        "checkpoint '{}' is for other B and C registers ({}, {})",
+                        path.display(),
+                        checkpoint.reg_b,
+                        checkpoint.reg_c
+                    ));
+                }
+                if checkpoint.end != self.end {
+                    return Err(miette!(
+                        "checkpoint '{}' is for another range end ({})",
+                        path.display(),
+                        checkpoint.end
+                    ));
+                }
+                next = next.max(checkpoint.next);
+            }
+        }
+
+        let start_time = Instant::now();
+        let start = next;
+        while next < self.end {
+            let chunk_end = next.saturating_add(self.chunk_size.max(1)).min(self.end);
+            let found = (next..chunk_end)
+                .into_par_iter()
+                .find_first(|&a| compiled.exec_till_self(Registers { a, ..registers }));
+            if let Some(a) = found {
+                self.save(machine, a)?;
+                return Ok(Outcome::Found(a));
+            }
+            next = chunk_end;
+            self.save(machine, next)?;
+
+            if let Some(on_progress) = &mut self.on_progress {
+                on_progress(&Progress {
+                    start,
+                    next,
+                    end: self.end,
+                    elapsed: start_time.elapsed(),
+                });
+            }
+
+            let cancelled = self
+                .cancel
+                .as_ref()
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+            let timed_out = self
+                .timeout
+                .is_some_and(|timeout| start_time.elapsed() >= timeout);
+            if (cancelled || timed_out) && next < self.end {
+                return Ok(Outcome::Stopped { next });
+            }
+        }
+
+        Ok(Outcome::Exhausted)
+    }
+
+    fn save(&self, machine: &Machine, next: usize) -> Result<()> {
+        match &self.checkpoint {
+            Some(path) => Checkpoint {
+                program: machine.program().clone(),
+                reg_b: machine.registers().b,
+                reg_c: machine.registers().c,
+                end: self.end,
+                next,
+            }
+            .save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Content of a checkpoint file:
+///
+/// ```text
+/// program: 0,3,5,4,3,0
+/// b: 0
+/// c: 0
+/// end: 18446744073709551615
+/// next: 117440
+/// ```
+///
+/// When the search found a value, `next` is that value. A checkpoint can
+/// only be resumed with the same program, B and C registers and range end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub program: Vec<usize>,
+    pub reg_b: usize,
+    pub reg_c: usize,
+    pub end: usize,
+    pub next: usize,
+}
+
+impl Checkpoint {
+    /// Returns `None` if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path).into_diagnostic()?;
+        let invalid = || miette!("invalid checkpoint file '{}'", path.display());
+
+        let mut program = None;
+        let (mut reg_b, mut reg_c, mut end, mut next) = (None, None, None, None);
+        let value = |value: &str| value.parse::<usize>().map_err(|_| invalid());
+        for line in content.lines() {
+            if let Some(values) = line.strip_prefix("program: ") {
+                program = Some(
+                    values
+                        .split(',')
+                        .map(|v| v.parse::<usize>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| invalid())?,
+                );
+            } else if let Some(v) = line.strip_prefix("b: ") {
+                reg_b = Some(value(v)?);
+            } else if let Some(v) = line.strip_prefix("c: ") {
+                reg_c = Some(value(v)?);
+            } else if let Some(v) = line.strip_prefix("end: ") {
+                end = Some(value(v)?);
+            } else if let Some(v) = line.strip_prefix("next: ") {
+                next = Some(value(v)?);
+            } else if !line.is_empty() {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Some(Self {
+            program: program.ok_or_else(invalid)?,
+            reg_b: reg_b.ok_or_else(invalid)?,
+            reg_c: reg_c.ok_or_else(invalid)?,
+            end: end.ok_or_else(invalid)?,
+            next: next.ok_or_else(invalid)?,
+        }))
+    }
+
+    /// Write the file atomically, so that an interruption never leaves a
+    /// corrupted checkpoint
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let program = self
+            .program
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let tmp = path.with_extension("tmp");
+        std::fs::write(
+            &tmp,
+            format!(
+                "program: {program}\nb: {}\nc: {}\nend: {}\nnext: {}\n",
+                self.reg_b, self.reg_c, self.end, self.next
+            ),
+        )
+        .into_diagnostic()?;
+        std::fs::rename(tmp, path).into_diagnostic()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROGRAM: [usize; 6] = [0, 3, 5, 4, 3, 0];
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("day17-{name}-{}.checkpoint", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn found() {
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let mut reports = Vec::new();
+        let outcome = BruteForce {
+            chunk_size: 10_000,
+            on_progress: Some(Box::new(|progress| reports.push(*progress))),
+            ..Default::default()
+        }
+        .run(&machine)
+        .unwrap();
+        assert_eq!(outcome, Outcome::Found(117440));
+        assert_eq!(reports.len(), 11);
+        assert_eq!(reports[10].next, 110_000);
+        assert_eq!(reports[10].checked(), 110_000);
+
+        let mut machine = machine;
+        assert_eq!(machine.fix_exec().unwrap(), Some(117440));
+    }
+
+    #[test]
+    fn exhausted() {
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let outcome = BruteForce {
+            start: 1000,
+            end: 100_000,
+            ..Default::default()
+        }
+        .run(&machine)
+        .unwrap();
+        assert_eq!(outcome, Outcome::Exhausted);
+    }
+
+    #[test]
+    fn cancel_and_resume() {
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let path = checkpoint_path("resume");
+
+        // Cancelled after the first chunk
+        let cancel = Arc::new(AtomicBool::new(true));
+        let outcome = BruteForce {
+            chunk_size: 50_000,
+            cancel: Some(cancel.clone()),
+            checkpoint: Some(path.clone()),
+            ..Default::default()
+        }
+        .run(&machine)
+        .unwrap();
+        assert_eq!(outcome, Outcome::Stopped { next: 50_000 });
+        assert_eq!(
+            Checkpoint::load(&path).unwrap(),
+            Some(Checkpoint {
+                program: PROGRAM.to_vec(),
+                reg_b: 0,
+                reg_c: 0,
+                end: usize::MAX,
+                next: 50_000
+            })
+        );
+
+        // Resumed, starting from the checkpoint
+        let mut reports = Vec::new();
+        let outcome = BruteForce {
+            chunk_size: 50_000,
+            timeout: Some(Duration::from_secs(3600)),
+            checkpoint: Some(path.clone()),
+            on_progress: Some(Box::new(|progress| reports.push(*progress))),
+            ..Default::default()
+        }
+        .run(&machine)
+        .unwrap();
+        assert_eq!(outcome, Outcome::Found(117440));
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].start, 50_000);
+        assert_eq!(Checkpoint::load(&path).unwrap().unwrap().next, 117440);
+
+        // Not the same program
+        let other = Machine::new(vec![0, 1, 5, 4, 3, 0], 0, 0, 0);
+        assert!(BruteForce {
+            checkpoint: Some(path.clone()),
+            ..Default::default()
+        }
+        .run(&other)
+        .is_err());
+
+        // Not the same registers
+        let other = Machine::new(PROGRAM.to_vec(), 0, 1, 0);
+        assert!(BruteForce {
+            checkpoint: Some(path.clone()),
+            ..Default::default()
+        }
+        .run(&other)
+        .is_err());
+
+        // Not the same end
+        assert!(BruteForce {
+            end: 200_000,
+            checkpoint: Some(path.clone()),
+            ..Default::default()
+        }
+        .run(&machine)
+        .is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn timeout() {
+        let machine = Machine::new(PROGRAM.to_vec(), 0, 0, 0);
+        let outcome = BruteForce {
+            chunk_size: 1000,
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        }
+        .run(&machine)
+        .unwrap();
+        assert_eq!(outcome, Outcome::Stopped { next: 1000 });
+    }
+
+    #[test]
+    fn invalid_checkpoint() {
+        let path = checkpoint_path("invalid");
+        std::fs::write(&path, "program: 1,2\nb: 0\nc: 0\nend: 9\nnext: x\n").unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+        std::fs::write(&path, "program: 1,2\nnext: 3\n").unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+        std::fs::write(&path, "program: 1,2\n").unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), None);
+    }
+}
@@ -34,10 +34,10 @@ mod part2_bench {
             .bench_values(|content| part2_cached::run(&content).unwrap());
     }
 
-    #[divan::bench(name = "2_cached_fx")]
-    fn cached_fx(bencher: divan::Bencher) {
+    #[divan::bench(name = "2_cached_rayon")]
+    fn cached_rayon(bencher: divan::Bencher) {
         bencher
             .with_inputs(|| read_input_u8!(None).unwrap())
-            .bench_values(|content| part2_cached_fx::run(&content).unwrap());
+            .bench_values(|content| part2_cached_rayon::run(&content).unwrap());
     }
 }
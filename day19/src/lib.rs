@@ -2,4 +2,4 @@ pub mod parse;
 pub mod part1;
 pub mod part2;
 pub mod part2_cached;
-pub mod part2_cached_fx;
+pub mod part2_cached_rayon;
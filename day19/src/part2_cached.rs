@@ -1,4 +1,4 @@
-use cached::{proc_macro::cached, Cached};
+use common::memo::Memo;
 use miette::Result;
 
 use crate::parse::parse;
@@ -6,41 +6,39 @@ use crate::parse::parse;
 pub fn run(content: &[u8]) -> Result<u64> {
     let data = parse(content)?;
 
-    COUNT_VALID_DESIGNS.lock().unwrap().cache_reset();
+    let mut memo = Memo::new();
     let result: usize = data
         .designs
         .iter()
-        .map(|design| count_valid_designs(design, &data.patterns))
+        .map(|design| count_valid_designs(design, &data.patterns, &mut memo))
         .sum();
 
-    // println!(
-    //     "cache misses: {:?}",
-    //     COUNT_VALID_DESIGNS.lock().unwrap().cache_misses()
-    // );
-    // println!(
-    //     "cache hits: {:?}",
-    //     COUNT_VALID_DESIGNS.lock().unwrap().cache_hits()
-    // );
+    // println!("cache: {:?}", memo.stats());
 
     Ok(result as u64)
 }
 
-#[cached(key = "Vec<u8>", convert = r#"{Vec::from({design})}"#)]
-fn count_valid_designs(design: &[u8], patterns: &[&[u8]]) -> usize {
+fn count_valid_designs<'a>(
+    design: &'a [u8],
+    patterns: &[&'a [u8]],
+    memo: &mut Memo<&'a [u8], usize>,
+) -> usize {
     if design.is_empty() {
         return 1;
     }
-    patterns
-        .iter()
-        .map(|pattern| {
-            if !design.starts_with(pattern) {
-                return 0;
-            }
-            let sub_design = &design[pattern.len()..];
+    memo.get_or_insert_with(design, |memo| {
+        patterns
+            .iter()
+            .map(|pattern| {
+                if !design.starts_with(pattern) {
+                    return 0;
+                }
+                let sub_design = &design[pattern.len()..];
 
-            count_valid_designs(sub_design, patterns)
-        })
-        .sum()
+                count_valid_designs(sub_design, patterns, memo)
+            })
+            .sum()
+    })
 }
 
 #[cfg(test)]
@@ -71,12 +69,9 @@ bbrgwb
 
     #[test]
     fn compare_base() {
-        // assert_eq!(
-        //     run(&INPUT_SAMPLE[1..]).unwrap(),
-        //     crate::part2::run(&INPUT_SAMPLE[1..]).unwrap()
-        // );
-
-        let input = common::read_input_u8!(None).unwrap();
-        assert_eq!(run(&input).unwrap(), crate::part2::run(&input).unwrap());
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part2::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
     }
 }
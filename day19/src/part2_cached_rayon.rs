@@ -0,0 +1,78 @@
+// Same as `part2_cached`, but processing the designs in parallel with a shared
+// cache
+use common::memo::SyncMemo;
+use miette::Result;
+use rayon::prelude::*;
+
+use crate::parse::parse;
+
+pub fn run(content: &[u8]) -> Result<u64> {
+    let data = parse(content)?;
+
+    let memo = SyncMemo::new();
+    let result: usize = data
+        .designs
+        .par_iter()
+        .map(|design| count_valid_designs(design, &data.patterns, &memo))
+        .sum();
+
+    Ok(result as u64)
+}
+
+fn count_valid_designs<'a>(
+    design: &'a [u8],
+    patterns: &[&'a [u8]],
+    memo: &SyncMemo<&'a [u8], usize>,
+) -> usize {
+    if design.is_empty() {
+        return 1;
+    }
+    memo.get_or_insert_with(design, |memo| {
+        patterns
+            .iter()
+            .map(|pattern| {
+                if !design.starts_with(pattern) {
+                    return 0;
+                }
+                let sub_design = &design[pattern.len()..];
+
+                count_valid_designs(sub_design, patterns, memo)
+            })
+            .sum()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb
+"#;
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 16);
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part2::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
+    }
+}
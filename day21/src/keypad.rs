@@ -0,0 +1,480 @@
+//! Keypad layouts and chains of robots pushing each other's buttons.
+//!
+//! A keypad is described as a small text grid, one character per key, with
+//! ` ` or `.` for the gaps:
+//!
+//! ```text
+//!  ^A
+//! <v>
+//! ```
+//!
+//! A [`Chain`] is a list of keypads: the first one is the door keypad, each
+//! following one is used to move the robot arm over the previous keypad, and
+//! the last one is used by the human.
+
+use std::{cmp::Reverse, collections::BinaryHeap, fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+use miette::{miette, Result};
+use rustc_hash::FxHashMap;
+
+pub const NUMERIC: &str = "789\n456\n123\n 0A";
+pub const DIRECTIONAL: &str = " ^A\n<v>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Activate,
+}
+
+impl Action {
+    pub const LIST: [Self; 5] = [
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Activate,
+    ];
+
+    /// Key of the action on a directional keypad
+    #[inline]
+    pub fn key(self) -> u8 {
+        match self {
+            Self::Up => b'^',
+            Self::Down => b'v',
+            Self::Left => b'<',
+            Self::Right => b'>',
+            Self::Activate => b'A',
+        }
+    }
+
+    pub fn from_key(key: u8) -> Option<Self> {
+        Self::LIST.into_iter().find(|action| action.key() == key)
+    }
+
+    /// Moved position, or `None` for [`Action::Activate`]
+    pub fn apply(self, (x, y): (i32, i32)) -> Option<(i32, i32)> {
+        match self {
+            Self::Up => Some((x, y - 1)),
+            Self::Down => Some((x, y + 1)),
+            Self::Left => Some((x - 1, y)),
+            Self::Right => Some((x + 1, y)),
+            Self::Activate => None,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key() as char)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keypad {
+    /// Rows of keys, `None` for the gaps
+    grid: Vec<Vec<Option<u8>>>,
+    positions: FxHashMap<u8, (i32, i32)>,
+}
+
+impl FromStr for Keypad {
+    type Err = miette::Report;
+
+    fn from_str(layout: &str) -> Result<Self> {
+        let grid = layout
+            .lines()
+            .map(|line| {
+                line.bytes()
+                    .map(|key| (key != b' ' && key != b'.').then_some(key))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut positions = FxHashMap::default();
+        for (y, row) in grid.iter().enumerate() {
+            for (x, key) in row.iter().enumerate() {
+                let Some(key) = *key else { continue };
+                if positions.insert(key, (x as i32, y as i32)).is_some() {
+                    return Err(miette!("key '{}' is on the keypad twice", key as char));
+                }
+            }
+        }
+        if !positions.contains_key(&b'A') {
+            return Err(miette!("the keypad has no 'A' key"));
+        }
+
+        Ok(Self { grid, positions })
+    }
+}
+
+impl Keypad {
+    pub fn numeric() -> Self {
+        NUMERIC.parse().unwrap()
+    }
+
+    pub fn directional() -> Self {
+        DIRECTIONAL.parse().unwrap()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = u8> + Clone + '_ {
+        self.positions.keys().copied().sorted()
+    }
+
+    pub fn position(&self, key: u8) -> Option<(i32, i32)> {
+        self.positions.get(&key).copied()
+    }
+
+    /// Key at a position, `None` for a gap or outside of the keypad
+    pub fn key_at(&self, (x, y): (i32, i32)) -> Option<u8> {
+        let row = self.grid.get(usize::try_from(y).ok()?)?;
+        *row.get(usize::try_from(x).ok()?)?
+    }
+}
+
+/// Cheapest way of moving the arm over a keypad and pushing a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Move {
+    /// Number of keys pushed by the human
+    cost: usize,
+    /// Moves of the arm, before pushing
+    path: Vec<Action>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Chain {
+    keypads: Vec<Keypad>,
+    /// For each keypad, the cheapest move between each pair of keys
+    moves: Vec<FxHashMap<(u8, u8), Move>>,
+}
+
+impl Chain {
+    /// `keypads[0]` is the door keypad and the last one is used by the human.
+    /// All the keypads but the first one must be directional, with only the
+    /// `^v<>A` keys.
+    pub fn new(keypads: Vec<Keypad>) -> Result<Self> {
+        if keypads.is_empty() {
+            return Err(miette!("a chain needs at least one keypad"));
+        }
+        for (idx, keypad) in keypads.iter().enumerate().skip(1) {
+            if let Some(action) = Action::LIST
+                .iter()
+                .find(|action| keypad.position(action.key()).is_none())
+            {
+                return Err(miette!(
+                    "keypad {idx} controls a robot but has no '{action}' key"
+                ));
+            }
+            if let Some(key) = keypad.keys().find(|&key| Action::from_key(key).is_none()) {
+                return Err(miette!(
+                    "keypad {idx} controls a robot but has a '{}' key",
+                    key as char
+                ));
+            }
+        }
+
+        // This is not a simple "manhattan distance" because the directional
+        // keys dont all have the same cost. If robot N must move left, robot
+        // N+1 will need to execute 4 moves to go from 'A' to '<' and push
+        // ("v<<A"). But if N moves right, N+1 only moves 2 ("vA"). Also,
+        // repeatedly moving in the same direction only cost 1 extra push each
+        // time. So the order of the moves matters.
+        //
+        // However, every single push on keypad N means that the arm over
+        // keypad N+1 ends over 'A', because it just pushed it. So the cost of
+        // moving between two keys of keypad N only depends on how the arm over
+        // N+1 moves, starting from and ending on 'A', and not on what happened
+        // before. This gives a dynamic programming over the keypads, starting
+        // from the human one where every push costs 1.
+        let last = keypads.len() - 1;
+        let mut moves = vec![FxHashMap::default(); keypads.len()];
+        for (from, to) in keypads[last].keys().cartesian_product(keypads[last].keys()) {
+            moves[last].insert(
+                (from, to),
+                Move {
+                    cost: 1,
+                    path: vec![],
+                },
+            );
+        }
+        for idx in (0..last).rev() {
+            let (current, next) = moves.split_at_mut(idx + 1);
+            for from in keypads[idx].keys() {
+                for (to, best) in best_moves(&keypads[idx], &next[0], from) {
+                    current[idx].insert((from, to), best);
+                }
+            }
+        }
+
+        Ok(Self { keypads, moves })
+    }
+
+    /// Numeric door keypad, `robots` directional keypads used by robots, and
+    /// the directional keypad of the human
+    pub fn standard(robots: usize) -> Self {
+        let mut keypads = vec![Keypad::numeric()];
+        keypads.extend(std::iter::repeat_n(Keypad::directional(), robots + 1));
+        Self::new(keypads).unwrap()
+    }
+
+    pub fn keypads(&self) -> &[Keypad] {
+        &self.keypads
+    }
+
+    /// Cheapest moves of the arm over keypad `keypad` to go from `from` to
+    /// `to`, before pushing `to`
+    pub fn path(&self, keypad: usize, from: u8, to: u8) -> Option<&[Action]> {
+        let m = self.moves.get(keypad)?.get(&(from, to))?;
+        Some(&m.path)
+    }
+
+    /// Number of pushes by the human to type the code on the door keypad
+    pub fn code_length(&self, code: &[u8]) -> Result<usize> {
+        std::iter::once(b'A')
+            .chain(code.iter().copied())
+            .tuple_windows()
+            .map(|(from, to)| {
+                self.moves[0]
+                    .get(&(from, to))
+                    .map(|m| m.cost)
+                    .ok_or_else(|| {
+                        miette!(
+                            "can't move from '{}' to '{}' on the door keypad",
+                            from as char,
+                            to as char
+                        )
+                    })
+            })
+            .sum()
+    }
+
+    /// Keys pushed by the human to type the code on the door keypad, with
+    /// one of the cheapest sequences. Fails if the sequence would be longer
+    /// than `max_len`, since it grows exponentially with the number of robots.
+    pub fn sequence(&self, code: &[u8], max_len: usize) -> Result<Vec<u8>> {
+        let len = self.code_length(code)?;
+        if len > max_len {
+            return Err(miette!(
+                "the sequence has {len} keys, more than the maximum of {max_len}"
+            ));
+        }
+
+        let mut keys = code.to_vec();
+        for keypad in 0..self.keypads.len() - 1 {
+            keys = std::iter::once(b'A')
+                .chain(keys)
+                .tuple_windows()
+                .flat_map(|(from, to)| {
+                    let path = &self.moves[keypad][&(from, to)].path;
+                    path.iter()
+                        .map(|action| action.key())
+                        .chain(std::iter::once(b'A'))
+                })
+                .collect();
+        }
+        Ok(keys)
+    }
+
+    /// Length of the code multiplied by its numeric part
+    pub fn complexity(&self, code: &[u8]) -> Result<usize> {
+        let value = code
+            .iter()
+            .filter(|b| b.is_ascii_digit())
+            .fold(0, |acc, b| acc * 10 + (b - b'0') as usize);
+        Ok(self.code_length(code)? * value)
+    }
+}
+
+/// Cheapest moves from `from` to all the keys of `keypad`, given the
+/// cheapest moves on the keypad controlling its arm.
+///
+/// This is a Dijkstra over the position of the arm and the last key pushed
+/// on the controlling keypad, so the gaps are avoided, with a detour if
+/// needed.
+fn best_moves(
+    keypad: &Keypad,
+    controls: &FxHashMap<(u8, u8), Move>,
+    from: u8,
+) -> FxHashMap<u8, Move> {
+    let cost =
+        |last: Action, action: Action| controls.get(&(last.key(), action.key())).map(|m| m.cost);
+
+    let start = (keypad.position(from).unwrap(), Action::Activate);
+    let mut costs = FxHashMap::from_iter([(start, 0)]);
+    let mut previous = FxHashMap::default();
+    let mut queue = BinaryHeap::from([Reverse((0, start))]);
+    while let Some(Reverse((current, (pos, last)))) = queue.pop() {
+        if costs[&(pos, last)] < current {
+            continue;
+        }
+        for action in [Action::Up, Action::Down, Action::Left, Action::Right] {
+            let next = action.apply(pos).unwrap();
+            let Some(cost) = cost(last, action) else {
+                continue;
+            };
+            if keypad.key_at(next).is_none() {
+                continue;
+            }
+            let state = (next, action);
+            if costs.get(&state).is_none_or(|&c| current + cost < c) {
+                costs.insert(state, current + cost);
+                previous.insert(state, (pos, last));
+                queue.push(Reverse((current + cost, state)));
+            }
+        }
+    }
+
+    let mut best = FxHashMap::<u8, ((i32, i32), Action, usize)>::default();
+    for (&(pos, last), &c) in &costs {
+        let Some(c) = cost(last, Action::Activate).map(|push| c + push) else {
+            continue;
+        };
+        let key = keypad.key_at(pos).unwrap();
+        if best.get(&key).is_none_or(|&(_, _, best)| c < best) {
+            best.insert(key, (pos, last, c));
+        }
+    }
+
+    best.into_iter()
+        .map(|(key, (pos, last, cost))| {
+            let mut path = Vec::new();
+            let mut state = (pos, last);
+            while state != start {
+                path.push(state.1);
+                state = previous[&state];
+            }
+            path.reverse();
+            (key, Move { cost, path })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use rustc_hash::FxHashSet;
+
+    use super::*;
+
+    /// Fewest pushes by the human to type the code, with a BFS over the
+    /// positions of all the arms
+    fn brute_force(keypads: &[Keypad], code: &[u8]) -> Option<usize> {
+        let start = keypads
+            .iter()
+            .map(|keypad| keypad.position(b'A').unwrap())
+            .collect::<Vec<_>>();
+        let last = keypads.len() - 1;
+        let mut seen = FxHashSet::default();
+        let mut queue = VecDeque::from([(start[..last].to_vec(), 0, 0)]);
+        while let Some((arms, typed, pushes)) = queue.pop_front() {
+            if typed == code.len() {
+                return Some(pushes);
+            }
+            if !seen.insert((arms.clone(), typed)) {
+                continue;
+            }
+            for &key in &keypads[last].keys().collect::<Vec<_>>() {
+                // Push `key` on the keypad `level`
+                let (mut arms, mut key, mut typed) = (arms.clone(), key, typed);
+                let mut level = last;
+                let valid = loop {
+                    if level == 0 {
+                        if code[typed] != key {
+                            break false;
+                        }
+                        typed += 1;
+                        break true;
+                    }
+                    level -= 1;
+                    match Action::from_key(key).unwrap().apply(arms[level]) {
+                        Some(pos) => {
+                            arms[level] = pos;
+                            break keypads[level].key_at(pos).is_some();
+                        }
+                        None => key = keypads[level].key_at(arms[level]).unwrap(),
+                    }
+                };
+                if valid {
+                    queue.push_back((arms, typed, pushes + 1));
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn layout() {
+        let keypad = Keypad::directional();
+        assert_eq!(keypad.position(b'^'), Some((1, 0)));
+        assert_eq!(keypad.key_at((0, 0)), None);
+        assert_eq!(keypad.key_at((3, 0)), None);
+        assert_eq!(keypad.key_at((-1, 1)), None);
+        assert_eq!(keypad.keys().collect::<Vec<_>>(), b"<>A^v");
+
+        assert!("12\n2A".parse::<Keypad>().is_err());
+        assert!("12\n34".parse::<Keypad>().is_err());
+        assert!(Chain::new(vec![Keypad::numeric(), Keypad::numeric()]).is_err());
+        let extra = "B^A\n<v>".parse::<Keypad>().unwrap();
+        assert!(Chain::new(vec![Keypad::numeric(), extra]).is_err());
+        assert!(Chain::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn gaps() {
+        let chain = Chain::standard(2);
+        let path = |keypad, from, to| chain.path(keypad, from, to).unwrap().to_vec();
+        // The gap forces going right first
+        assert_eq!(path(1, b'<', b'^'), vec![Action::Right, Action::Up]);
+        assert_eq!(path(1, b'A', b'A'), vec![]);
+        // The cheapest path goes left first, unless over the gap
+        assert_eq!(path(0, b'9', b'1')[0], Action::Left);
+        assert_eq!(path(0, b'A', b'1')[0], Action::Up);
+        assert_eq!(path(0, b'0', b'7')[0], Action::Up);
+        // The human pushes directly
+        assert_eq!(path(3, b'<', b'A'), vec![]);
+
+        // Detour around a gap
+        let keypad = "123\n4.5\n6A7\n890".parse::<Keypad>().unwrap();
+        let chain = Chain::new(vec![keypad, Keypad::directional()]).unwrap();
+        assert_eq!(chain.path(0, b'2', b'9').unwrap().len(), 5);
+
+        // Isolated key
+        let keypad = "1.2\n...\nA3.".parse::<Keypad>().unwrap();
+        let chain = Chain::new(vec![keypad, Keypad::directional()]).unwrap();
+        assert!(chain.code_length(b"3").is_ok());
+        assert!(chain.code_length(b"2").is_err());
+    }
+
+    #[test]
+    fn chains() {
+        let codes: [&[u8]; 5] = [b"029A", b"980A", b"179A", b"456A", b"379A"];
+        let layouts = [
+            (NUMERIC, DIRECTIONAL),
+            ("A01\n234\n.56\n789", DIRECTIONAL),
+            (NUMERIC, "<^>\n.vA"),
+            ("123\n4.5\n6A7\n890", "^.A\n<v>"),
+        ];
+        for (door, directional) in layouts {
+            for robots in 0..3 {
+                let mut keypads = vec![door.parse::<Keypad>().unwrap()];
+                keypads.extend(vec![directional.parse::<Keypad>().unwrap(); robots + 1]);
+                let chain = Chain::new(keypads.clone()).unwrap();
+                for code in codes {
+                    assert_eq!(
+                        Some(chain.code_length(code).unwrap()),
+                        brute_force(&keypads, code),
+                        "{door:?} {directional:?} {robots} {}",
+                        std::str::from_utf8(code).unwrap()
+                    );
+                }
+            }
+        }
+
+        // The human types directly on the door keypad
+        let chain = Chain::new(vec![Keypad::numeric()]).unwrap();
+        assert_eq!(chain.code_length(b"029A").unwrap(), 4);
+    }
+}
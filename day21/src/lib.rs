@@ -1,3 +1,7 @@
+pub mod keypad;
 pub mod parse;
 pub mod part1;
+pub mod part1_hardcoded;
 pub mod part2;
+pub mod part2_hardcoded;
+pub mod simulate;
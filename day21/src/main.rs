@@ -1,8 +1,11 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
-use miette::Result;
+use day21::{
+    keypad::{self, Chain, Keypad},
+    part1, part1_hardcoded, part2, part2_hardcoded, simulate,
+};
+use miette::{IntoDiagnostic, Result};
 use std::path::PathBuf;
-use day21::{part1, part2};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -18,6 +21,67 @@ enum Command {
 
     /// Part 2
     Part2 { file: Option<PathBuf> },
+
+    /// Part 1, with the original hardcoded keypads
+    Part1Hardcoded { file: Option<PathBuf> },
+
+    /// Part 2, with the original hardcoded keypads
+    Part2Hardcoded { file: Option<PathBuf> },
+
+    /// Sum of the complexities with any number of robots and keypad layouts
+    Chain {
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        chain: ChainArgs,
+    },
+
+    /// Keys pushed by the human to type a code
+    Sequence {
+        code: String,
+
+        #[command(flatten)]
+        chain: ChainArgs,
+    },
+
+    /// Replay the keys pushed by the human, and print the typed code
+    Simulate {
+        keys: String,
+
+        #[command(flatten)]
+        chain: ChainArgs,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct ChainArgs {
+    /// Number of robots using a directional keypad
+    #[arg(short, long, default_value_t = 2)]
+    robots: usize,
+
+    /// Layout of the door keypad (numeric keypad by default)
+    #[arg(long)]
+    door: Option<PathBuf>,
+
+    /// Layout of the directional keypads
+    #[arg(long)]
+    directional: Option<PathBuf>,
+}
+
+impl ChainArgs {
+    fn chain(self) -> Result<Chain> {
+        let mut keypads = vec![read_keypad(self.door, keypad::NUMERIC)?];
+        let directional = read_keypad(self.directional, keypad::DIRECTIONAL)?;
+        keypads.extend(std::iter::repeat_n(directional, self.robots + 1));
+        Chain::new(keypads)
+    }
+}
+
+fn read_keypad(path: Option<PathBuf>, default: &str) -> Result<Keypad> {
+    match path {
+        Some(path) => std::fs::read_to_string(path).into_diagnostic()?.parse(),
+        None => default.parse(),
+    }
 }
 
 fn main() -> Result<()> {
@@ -30,6 +94,27 @@ fn main() -> Result<()> {
         Some(Command::Part2 { file }) => {
             println!("Result: {}", part2::run(&read_input_u8!(file)?)?)
         }
+        Some(Command::Part1Hardcoded { file }) => {
+            println!("Result: {}", part1_hardcoded::run(&read_input_u8!(file)?)?)
+        }
+        Some(Command::Part2Hardcoded { file }) => {
+            println!("Result: {}", part2_hardcoded::run(&read_input_u8!(file)?)?)
+        }
+        Some(Command::Chain { file, chain }) => {
+            println!(
+                "Result: {}",
+                part1::run_chain(&read_input_u8!(file)?, &chain.chain()?)?
+            )
+        }
+        Some(Command::Sequence { code, chain }) => {
+            let keys = chain.chain()?.sequence(code.as_bytes(), 1 << 24)?;
+            println!("{}", String::from_utf8_lossy(&keys));
+        }
+        Some(Command::Simulate { keys, chain }) => {
+            let chain = chain.chain()?;
+            let typed = simulate::Simulator::new(&chain).run(keys.as_bytes())?;
+            println!("Typed: {}", String::from_utf8_lossy(&typed));
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
@@ -1,19 +1,9 @@
 use miette::Result;
 
-use crate::parse::parse;
-use crate::part1::*;
+use crate::{keypad::Chain, part1::run_chain};
 
 pub fn run(content: &[u8]) -> Result<u64> {
-    let codes = parse(content)?;
-
-    let mut depth_cache = init_dir_cost_cache();
-
-    let result: usize = codes
-        .iter()
-        .map(|code| get_code_length(code, 24, &mut depth_cache))
-        .sum();
-
-    Ok(result as u64)
+    run_chain(content, &Chain::standard(25))
 }
 
 #[cfg(test)]
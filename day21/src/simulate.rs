@@ -0,0 +1,171 @@
+//! Replay of the keys pushed by the human through a chain of keypads, to
+//! check the sequences end-to-end.
+
+use miette::{miette, Result};
+
+use crate::keypad::{Action, Chain, Keypad};
+
+/// State of all the robot arms while the human pushes keys
+#[derive(Debug, Clone)]
+pub struct Simulator<'a> {
+    keypads: &'a [Keypad],
+    /// Position of the arm over each keypad, but the human one
+    arms: Vec<(i32, i32)>,
+    typed: Vec<u8>,
+    pushes: usize,
+}
+
+impl<'a> Simulator<'a> {
+    /// All the arms start over `A`
+    pub fn new(chain: &'a Chain) -> Self {
+        let keypads = chain.keypads();
+        let arms = keypads[..keypads.len() - 1]
+            .iter()
+            .map(|keypad| keypad.position(b'A').unwrap())
+            .collect();
+        Self {
+            keypads,
+            arms,
+            typed: vec![],
+            pushes: 0,
+        }
+    }
+
+    /// Keys typed on the door keypad so far
+    pub fn typed(&self) -> &[u8] {
+        &self.typed
+    }
+
+    /// Key under each arm, from the door keypad
+    pub fn arms(&self) -> Vec<u8> {
+        self.arms
+            .iter()
+            .zip(self.keypads)
+            .map(|(&pos, keypad)| keypad.key_at(pos).unwrap())
+            .collect()
+    }
+
+    /// Push a key on the human keypad. Fails if the key doesn't exist, or if
+    /// an arm would end over a gap. The state is unchanged on error.
+    pub fn push(&mut self, key: u8) -> Result<()> {
+        let last = self.keypads.len() - 1;
+        if self.keypads[last].position(key).is_none() {
+            return Err(miette!(
+                "push {}: no key '{}' on the human keypad",
+                self.pushes,
+                key as char
+            ));
+        }
+
+        // Each push on a keypad either moves the arm over the previous one,
+        // or pushes the key under it
+        let mut key = key;
+        for level in (0..last).rev() {
+            // `Chain` only accepts action keys on the keypads above the door
+            let action = Action::from_key(key).unwrap();
+            match action.apply(self.arms[level]) {
+                Some(pos) if self.keypads[level].key_at(pos).is_none() => {
+                    return Err(miette!(
+                        "push {}: the arm over keypad {level} would leave the keys",
+                        self.pushes
+                    ));
+                }
+                Some(pos) => {
+                    self.arms[level] = pos;
+                    self.pushes += 1;
+                    return Ok(());
+                }
+                None => key = self.keypads[level].key_at(self.arms[level]).unwrap(),
+            }
+        }
+        self.typed.push(key);
+        self.pushes += 1;
+        Ok(())
+    }
+
+    /// Push all the keys, and return the keys typed on the door keypad
+    pub fn run(mut self, keys: &[u8]) -> Result<Vec<u8>> {
+        for &key in keys {
+            self.push(key)?;
+        }
+        Ok(self.typed)
+    }
+}
+
+/// Check that the keys pushed by the human type `code` on the door keypad
+pub fn verify(chain: &Chain, keys: &[u8], code: &[u8]) -> Result<()> {
+    let typed = Simulator::new(chain).run(keys)?;
+    if typed != code {
+        return Err(miette!(
+            "typed '{}' instead of '{}'",
+            String::from_utf8_lossy(&typed),
+            String::from_utf8_lossy(code)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keypad::Keypad;
+
+    use super::*;
+
+    const CODES: [&[u8]; 5] = [b"029A", b"980A", b"179A", b"456A", b"379A"];
+
+    #[test]
+    fn sample() {
+        // From the puzzle
+        let keys = b"<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A";
+        let chain = Chain::standard(2);
+        verify(&chain, keys, b"029A").unwrap();
+        assert!(verify(&chain, keys, b"029").is_err());
+        assert!(verify(&chain, &keys[..keys.len() - 1], b"029A").is_err());
+    }
+
+    #[test]
+    fn sequences() {
+        for robots in 0..5 {
+            let chain = Chain::standard(robots);
+            for code in CODES {
+                let keys = chain.sequence(code, 1 << 20).unwrap();
+                assert_eq!(keys.len(), chain.code_length(code).unwrap());
+                verify(&chain, &keys, code).unwrap();
+            }
+        }
+
+        let chain = Chain::standard(25);
+        assert!(chain.sequence(b"029A", 1 << 20).is_err());
+
+        // Detour around a gap
+        let door = "123\n4.5\n6A7\n890".parse::<Keypad>().unwrap();
+        let chain = Chain::new(vec![door, Keypad::directional(), Keypad::directional()]).unwrap();
+        let keys = chain.sequence(b"2901A", 1 << 20).unwrap();
+        verify(&chain, &keys, b"2901A").unwrap();
+    }
+
+    #[test]
+    fn gaps() {
+        // The human uses the directional keypad of the robot at the door
+        let chain = Chain::standard(0);
+        let mut simulator = Simulator::new(&chain);
+        simulator.push(b'<').unwrap();
+        assert_eq!(simulator.arms(), b"0");
+        let err = simulator.push(b'<').unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "push 1: the arm over keypad 0 would leave the keys"
+        );
+        assert_eq!(simulator.arms(), b"0");
+        simulator.push(b'A').unwrap();
+        assert_eq!(simulator.typed(), b"0");
+        assert!(simulator.push(b'0').is_err());
+
+        // The first robot goes over the gap of its directional keypad
+        let chain = Chain::standard(1);
+        assert!(verify(&chain, b"<", b"").is_ok());
+        assert!(verify(&chain, b"<A", b"").is_ok());
+        assert!(verify(&chain, b"^A", b"").is_err());
+        assert!(verify(&chain, b"v<A<A", b"").is_err());
+    }
+}
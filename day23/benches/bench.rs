@@ -15,6 +15,13 @@ mod part1_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part1::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "1_ident")]
+    fn ident(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part1_ident::run(&content).unwrap());
+    }
 }
 
 mod part2_bench {
@@ -54,4 +61,11 @@ mod part2_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part2_hukasu::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "5_ident")]
+    fn ident(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part2_ident::run(&content).unwrap());
+    }
 }
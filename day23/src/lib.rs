@@ -1,7 +1,9 @@
 pub mod parse;
 pub mod part1;
+pub mod part1_ident;
 pub mod part2;
 pub mod part2_bf_rayon;
 pub mod part2_brute_force;
 pub mod part2_hukasu;
+pub mod part2_ident;
 pub mod part2_optimize;
@@ -1,13 +1,17 @@
 use core::str;
 use std::fmt::{Debug, Display};
 
-use common::error::AdventError;
+use common::{
+    error::AdventError,
+    ident::{Ident, Interner},
+};
 use rustc_hash::FxHashMap;
 use winnow::{
     ascii::line_ending,
     combinator::{repeat, separated_pair, terminated, trace},
     prelude::*,
-    token::take,
+    stream::AsChar,
+    token::{take, take_while},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -59,3 +63,65 @@ fn parse_line(input: &mut &[u8]) -> PResult<(Machine, Machine)> {
     )
     .parse_next(input)
 }
+
+/// Network where the machine names are interned, so the connections can be
+/// indexed by machine
+#[derive(Debug, Clone)]
+pub struct Network {
+    /// Machine names. Ids are sorted by name.
+    pub names: Interner,
+    /// For each machine, the connected machines with a greater id, sorted
+    pub connections: Vec<Vec<Ident>>,
+    /// Adjacency matrix (`names.len()` x `names.len()`)
+    pub matrix: Vec<bool>,
+}
+
+impl Network {
+    pub fn is_connected(&self, m1: Ident, m2: Ident) -> bool {
+        self.matrix[m1.index() * self.names.len() + m2.index()]
+    }
+}
+
+/// Same as [`parse`], but machine names can have any length and are interned
+pub fn parse_network(content: &[u8]) -> Result<Network, AdventError> {
+    let mut names = Interner::new();
+    let links: Vec<_> = trace(
+        "parser",
+        repeat(
+            1..,
+            terminated(separated_pair(parse_name, b'-', parse_name), line_ending),
+        ),
+    )
+    .parse(content)?;
+    let links = links
+        .into_iter()
+        .map(|(m1, m2): (&str, &str)| (names.intern(m1), names.intern(m2)))
+        .collect::<Vec<_>>();
+
+    let remap = names.sort_by_name();
+    let count = names.len();
+    let mut connections = vec![Vec::new(); count];
+    let mut matrix = vec![false; count * count];
+    for (m1, m2) in links {
+        let (m1, m2) = (remap[m1.index()], remap[m2.index()]);
+        let (m1, m2) = if m1 < m2 { (m1, m2) } else { (m2, m1) };
+        connections[m1.index()].push(m2);
+        matrix[m1.index() * count + m2.index()] = true;
+        matrix[m2.index() * count + m1.index()] = true;
+    }
+    connections.iter_mut().for_each(|v| v.sort());
+
+    Ok(Network {
+        names,
+        connections,
+        matrix,
+    })
+}
+
+fn parse_name<'i>(input: &mut &'i [u8]) -> PResult<&'i str> {
+    trace(
+        "parse_name",
+        take_while(1.., AsChar::is_alphanum).try_map(str::from_utf8),
+    )
+    .parse_next(input)
+}
@@ -0,0 +1,143 @@
+use common::ident::Ident;
+use miette::Result;
+
+use crate::parse::{parse_network, Network};
+
+/// Same as `part1`, but with interned machine names and vector-indexed
+/// connections instead of hashmaps
+pub fn run(content: &[u8]) -> Result<u64> {
+    let network = parse_network(content)?;
+    let groups = filter_chief(&network, &get_groups(&network));
+    Ok(groups.len() as u64)
+}
+
+fn filter_chief(network: &Network, groups: &[[Ident; 3]]) -> Vec<[Ident; 3]> {
+    groups
+        .iter()
+        .filter(|group| {
+            group
+                .iter()
+                .any(|m| network.names.name(*m).starts_with('t'))
+        })
+        .copied()
+        .collect::<Vec<_>>()
+}
+
+fn get_groups(network: &Network) -> Vec<[Ident; 3]> {
+    // Ids are sorted by name and connections only go to greater ids, so the
+    // groups are generated already sorted
+    network
+        .connections
+        .iter()
+        .enumerate()
+        .flat_map(|(m1, v1)| {
+            let m1 = Ident::from_index(m1);
+            v1.iter().flat_map(move |&m2| {
+                network.connections[m2.index()]
+                    .iter()
+                    .filter(move |&&m3| network.is_connected(m1, m3))
+                    .map(move |&m3| [m1, m2, m3])
+            })
+        })
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+"#;
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), 7);
+    }
+
+    #[test]
+    fn groups() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+        let output = "[(aq, cg, yn), (aq, vc, wq), (co, de, ka), (co, de, ta), (co, ka, ta), (de, ka, ta), (kh, qp, ub), (qp, td, wh), (tb, vc, wq), (tc, td, wh), (td, wh, yn), (ub, vc, wq)]";
+        let network = parse_network(input).unwrap();
+        let groups = get_groups(&network);
+
+        let groups_str = format_groups(&network, &groups);
+
+        assert_eq!(groups_str, output);
+    }
+
+    #[test]
+    fn groups_chief() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+        let output = "[(co, de, ta), (co, ka, ta), (de, ka, ta), (qp, td, wh), (tb, vc, wq), (tc, td, wh), (td, wh, yn)]";
+        let network = parse_network(input).unwrap();
+        let groups = get_groups(&network);
+        let groups = filter_chief(&network, &groups);
+
+        let groups_str = format_groups(&network, &groups);
+
+        assert_eq!(groups_str, output);
+    }
+
+    #[test]
+    fn long_names() {
+        let input = b"kh-tc\nkh-tom\ntc-tom\nkh-harry\n";
+
+        assert_eq!(run(input).unwrap(), 1);
+    }
+
+    fn format_groups(network: &Network, groups: &[[Ident; 3]]) -> String {
+        let groups = groups
+            .iter()
+            .map(|group| {
+                let [m1, m2, m3] = group.map(|m| network.names.display(m));
+                format!("({m1}, {m2}, {m3})")
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", groups.join(", "))
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part1::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+// Same as `part2_hukasu`, but with interned machine names and an adjacency
+// matrix instead of a hashset of connections
+use common::ident::Ident;
+use itertools::Itertools;
+use miette::Result;
+
+use crate::parse::{parse_network, Network};
+
+pub fn run(content: &[u8]) -> Result<String> {
+    let network = parse_network(content)?;
+
+    let mut computer_groups = Vec::with_capacity(500);
+    network
+        .connections
+        .iter()
+        .enumerate()
+        .for_each(|(pc, node_connections)| {
+            get_fully_connected(
+                node_connections,
+                &network,
+                vec![Ident::from_index(pc)],
+                &mut computer_groups,
+            );
+        });
+
+    // Ids are sorted by name, and each group was built in increasing id order
+    Ok(computer_groups
+        .into_iter()
+        .max_by_key(|group| group.len())
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|pc| network.names.display(pc))
+                .join(",")
+        })
+        .unwrap())
+}
+
+fn get_fully_connected(
+    pc_connections: &[Ident],
+    network: &Network,
+    mut partial: Vec<Ident>,
+    fully_connected: &mut Vec<Vec<Ident>>,
+) {
+    let [head_node, tail @ ..] = pc_connections else {
+        fully_connected.push(partial);
+        return;
+    };
+
+    get_fully_connected(tail, network, partial.clone(), fully_connected);
+
+    if partial
+        .iter()
+        .skip(1)
+        .all(|group_node| network.is_connected(*head_node, *group_node))
+    {
+        partial.push(*head_node);
+        get_fully_connected(tail, network, partial, fully_connected);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+"#;
+    // cspell:enable
+
+    #[test]
+    fn sample() {
+        let input = &INPUT_SAMPLE[1..]; // remove leading \n
+
+        assert_eq!(run(input).unwrap(), "co,de,ka,ta");
+    }
+
+    #[test]
+    fn long_names() {
+        let input = b"alice-bob\nbob-carol\ncarol-alice\ncarol-dave\n";
+
+        assert_eq!(run(input).unwrap(), "alice,bob,carol");
+    }
+
+    #[test]
+    fn compare_base() {
+        assert_eq!(
+            run(&INPUT_SAMPLE[1..]).unwrap(),
+            crate::part2::run(&INPUT_SAMPLE[1..]).unwrap()
+        );
+    }
+}
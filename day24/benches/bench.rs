@@ -26,4 +26,59 @@ mod part2_bench {
             .with_inputs(|| read_input_u8!(None).unwrap())
             .bench_values(|content| part2::run(&content).unwrap());
     }
+
+    #[divan::bench(name = "1_rules")]
+    fn rules(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| read_input_u8!(None).unwrap())
+            .bench_values(|content| part2_rules::run(&content).unwrap());
+    }
+}
+
+mod add_bench {
+    use super::*;
+
+    const X: u64 = 0x1234_5678_9abc;
+    const Y: u64 = 0x0fed_cba9_8765;
+
+    #[divan::bench(name = "0_machine")]
+    fn machine(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| parse::parse(&read_input_u8!(None).unwrap()).unwrap())
+            .bench_local_values(|mut machine| {
+                (0..compiled::LANES as u64)
+                    .map(|lane| machine.add(X + lane, Y).2)
+                    .sum::<u64>()
+            });
+    }
+
+    #[divan::bench(name = "1_compiled")]
+    fn compiled(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| {
+                let machine = parse::parse(&read_input_u8!(None).unwrap()).unwrap();
+                machine.compile().unwrap()
+            })
+            .bench_local_values(|compiled| {
+                (0..compiled::LANES as u64)
+                    .map(|lane| compiled.add(X + lane, Y))
+                    .sum::<u64>()
+            });
+    }
+
+    #[divan::bench(name = "2_lanes")]
+    fn lanes(bencher: divan::Bencher) {
+        bencher
+            .with_inputs(|| {
+                let machine = parse::parse(&read_input_u8!(None).unwrap()).unwrap();
+                machine.compile().unwrap()
+            })
+            .bench_local_values(|compiled| {
+                let x = std::array::from_fn(|lane| X + lane as u64);
+                compiled
+                    .add_lanes(&x, &[Y; compiled::LANES])
+                    .iter()
+                    .sum::<u64>()
+            });
+    }
 }
@@ -0,0 +1,458 @@
+//! Equivalence checking of a [`Machine`] against a reference function, with
+//! reduced ordered binary decision diagrams (ROBDDs).
+//!
+//! Each output bit of the circuit is computed symbolically as a BDD over the
+//! X/Y inputs, and so is the reference. BDDs are canonical, so a circuit bit
+//! matches the reference bit iff both are the same node. Otherwise, any
+//! assignment satisfying their XOR is a counterexample.
+//!
+//! The variables are ordered `x0, y0, x1, y1, ...`, which keeps the BDDs of
+//! an adder linear in the number of bits. The BDDs of a multiplier grow
+//! exponentially whatever the order, so only small ones can be checked.
+
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use miette::Result;
+use rustc_hash::FxHashMap;
+
+use crate::machine::{mask, Machine, Operator, Wire};
+
+/// Index of a node in a [`Bdd`]
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    var: usize,
+    low: NodeId,
+    high: NodeId,
+}
+
+/// BDD manager: all the nodes, shared between the diagrams
+#[derive(Debug, Clone)]
+pub struct Bdd {
+    nodes: Vec<Node>,
+    unique: FxHashMap<Node, NodeId>,
+    cache: FxHashMap<(Operator, NodeId, NodeId), NodeId>,
+}
+
+impl Default for Bdd {
+    fn default() -> Self {
+        // Terminals, with a variable after all the others
+        let terminal = Node {
+            var: usize::MAX,
+            low: 0,
+            high: 0,
+        };
+        Self {
+            nodes: vec![terminal, terminal],
+            unique: Default::default(),
+            cache: Default::default(),
+        }
+    }
+}
+
+impl Bdd {
+    pub const FALSE: NodeId = 0;
+    pub const TRUE: NodeId = 1;
+
+    pub fn constant(value: bool) -> NodeId {
+        value as NodeId
+    }
+
+    /// Diagram of variable `var`
+    pub fn var(&mut self, var: usize) -> NodeId {
+        self.node(var, Self::FALSE, Self::TRUE)
+    }
+
+    /// Number of nodes of the manager, shared between all the diagrams
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn not(&mut self, a: NodeId) -> NodeId {
+        self.xor(a, Self::TRUE)
+    }
+
+    pub fn and(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.apply(Operator::And, a, b)
+    }
+
+    pub fn or(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.apply(Operator::Or, a, b)
+    }
+
+    pub fn xor(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.apply(Operator::Xor, a, b)
+    }
+
+    /// Value of the function for the variables given by `vars`
+    pub fn eval(&self, mut node: NodeId, vars: impl Fn(usize) -> bool) -> bool {
+        while node > Self::TRUE {
+            let Node { var, low, high } = self.nodes[node];
+            node = if vars(var) { high } else { low };
+        }
+        node == Self::TRUE
+    }
+
+    /// An assignment making the function true, as `(variable, value)` pairs
+    /// (the missing variables can take any value), or `None` if it is always
+    /// false
+    pub fn any_sat(&self, mut node: NodeId) -> Option<Vec<(usize, bool)>> {
+        if node == Self::FALSE {
+            return None;
+        }
+        // Every non-terminal node leads to TRUE on at least one side
+        let mut assignment = Vec::new();
+        while node > Self::TRUE {
+            let Node { var, low, high } = self.nodes[node];
+            let value = low == Self::FALSE;
+            assignment.push((var, value));
+            node = if value { high } else { low };
+        }
+        Some(assignment)
+    }
+
+    /// Reduced node: no redundant test, no duplicate
+    fn node(&mut self, var: usize, low: NodeId, high: NodeId) -> NodeId {
+        if low == high {
+            return low;
+        }
+        let node = Node { var, low, high };
+        *self.unique.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+
+    fn apply(&mut self, operator: Operator, a: NodeId, b: NodeId) -> NodeId {
+        if a <= Self::TRUE && b <= Self::TRUE {
+            let (a, b) = (a == Self::TRUE, b == Self::TRUE);
+            return Self::constant(match operator {
+                Operator::And => a & b,
+                Operator::Or => a | b,
+                Operator::Xor => a ^ b,
+                Operator::Not => !a,
+            });
+        }
+        // All the operators are commutative
+        let (a, b) = (a.min(b), a.max(b));
+        if let Some(&node) = self.cache.get(&(operator, a, b)) {
+            return node;
+        }
+
+        let var = self.nodes[a].var.min(self.nodes[b].var);
+        let cofactors = |node: NodeId| match self.nodes[node] {
+            Node { var: v, low, high } if v == var => (low, high),
+            _ => (node, node),
+        };
+        let ((a_low, a_high), (b_low, b_high)) = (cofactors(a), cofactors(b));
+        let low = self.apply(operator, a_low, b_low);
+        let high = self.apply(operator, a_high, b_high);
+        let node = self.node(var, low, high);
+        self.cache.insert((operator, a, b), node);
+        node
+    }
+}
+
+/// Variable of input bit `n` of X
+pub fn x_var(n: usize) -> usize {
+    2 * n
+}
+
+/// Variable of input bit `n` of Y
+pub fn y_var(n: usize) -> usize {
+    2 * n + 1
+}
+
+/// Output bits of a machine as BDDs over its inputs
+#[derive(Debug, Clone)]
+pub struct Symbolic {
+    pub bdd: Bdd,
+    pub x: Vec<NodeId>,
+    pub y: Vec<NodeId>,
+    pub z: Vec<NodeId>,
+}
+
+impl Symbolic {
+    /// Fails if the machine can't be [compiled](Machine::compile)
+    pub fn new(machine: &Machine) -> Result<Self> {
+        let compiled = machine.compile()?;
+        let bits = machine.i_bits as usize;
+
+        let mut bdd = Bdd::default();
+        let x = (0..bits).map(|n| bdd.var(x_var(n))).collect::<Vec<_>>();
+        let y = (0..bits).map(|n| bdd.var(y_var(n))).collect::<Vec<_>>();
+
+        let mut values = FxHashMap::default();
+        for (&wire, &value) in &machine.init {
+            values.insert(wire, Bdd::constant(value));
+        }
+        for n in 0..bits {
+            values.insert(Wire::get_x(n as u16), x[n]);
+            values.insert(Wire::get_y(n as u16), y[n]);
+        }
+        for (operator, in1, in2, out) in compiled.gates() {
+            let (in1, in2) = (values[&in1], values[&in2]);
+            let value = match operator {
+                Operator::Not => bdd.not(in1),
+                _ => bdd.apply(operator, in1, in2),
+            };
+            values.insert(out, value);
+        }
+        let z = (0..machine.o_bits)
+            .map(|n| values[&Wire::get_z(n)])
+            .collect();
+
+        Ok(Self { bdd, x, y, z })
+    }
+}
+
+/// Function a machine is checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Reference {
+    /// `z = x + y`
+    Add,
+    /// `z = x & y`
+    And,
+    /// `z = x * y`
+    Mul,
+}
+
+impl Reference {
+    /// Value of the function, on `bits` bits
+    pub fn eval(&self, x: u64, y: u64, bits: usize) -> u64 {
+        let value = match self {
+            Reference::Add => x.wrapping_add(y),
+            Reference::And => x & y,
+            Reference::Mul => x.wrapping_mul(y),
+        };
+        value & mask(bits)
+    }
+
+    /// The `bits` output bits of the function as BDDs
+    pub fn outputs(&self, bdd: &mut Bdd, x: &[NodeId], y: &[NodeId], bits: usize) -> Vec<NodeId> {
+        let bit = |v: &[NodeId], n: usize| v.get(n).copied().unwrap_or(Bdd::FALSE);
+        match self {
+            Reference::Add => add(bdd, x, y, bits),
+            Reference::And => (0..bits).map(|n| bdd.and(bit(x, n), bit(y, n))).collect(),
+            Reference::Mul => {
+                // Sum of the partial products `x * y[n] << n`
+                let mut sum = vec![Bdd::FALSE; bits];
+                for (n, &yn) in y.iter().enumerate().take(bits) {
+                    let partial = (0..bits)
+                        .map(|m| match m.checked_sub(n) {
+                            Some(m) => bdd.and(bit(x, m), yn),
+                            None => Bdd::FALSE,
+                        })
+                        .collect::<Vec<_>>();
+                    sum = add(bdd, &sum, &partial, bits);
+                }
+                sum
+            }
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Reference::Add => "x + y",
+            Reference::And => "x & y",
+            Reference::Mul => "x * y",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Ripple-carry addition of BDDs, on `bits` bits
+fn add(bdd: &mut Bdd, x: &[NodeId], y: &[NodeId], bits: usize) -> Vec<NodeId> {
+    let bit = |v: &[NodeId], n: usize| v.get(n).copied().unwrap_or(Bdd::FALSE);
+    let mut carry = Bdd::FALSE;
+    (0..bits)
+        .map(|n| {
+            let (a, b) = (bit(x, n), bit(y, n));
+            let half = bdd.xor(a, b);
+            let sum = bdd.xor(half, carry);
+            let generate = bdd.and(a, b);
+            let propagate = bdd.and(half, carry);
+            carry = bdd.or(generate, propagate);
+            sum
+        })
+        .collect()
+}
+
+/// Inputs for which the machine differs from the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counterexample {
+    pub x: u64,
+    pub y: u64,
+    /// Lowest differing output bit
+    pub bit: usize,
+    pub expected: u64,
+    pub got: u64,
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "x={}, y={}: expected {}, got {} (bit {} differs)",
+            self.x, self.y, self.expected, self.got, self.bit
+        )
+    }
+}
+
+/// Check whether the machine computes the reference function on its output
+/// bits, for all the inputs. Returns `None` if it does, or a counterexample.
+pub fn check(machine: &Machine, reference: Reference) -> Result<Option<Counterexample>> {
+    let Symbolic { mut bdd, x, y, z } = Symbolic::new(machine)?;
+    let expected = reference.outputs(&mut bdd, &x, &y, z.len());
+
+    let Some((bit, diff)) = z
+        .iter()
+        .zip(&expected)
+        .enumerate()
+        .find(|(_, (z, expected))| z != expected)
+        .map(|(bit, (&z, &expected))| (bit, bdd.xor(z, expected)))
+    else {
+        return Ok(None);
+    };
+
+    // The XOR of two different canonical diagrams is satisfiable
+    let assignment = bdd.any_sat(diff).expect("different BDDs");
+    let (mut x, mut y) = (0, 0);
+    for (var, value) in assignment {
+        match var % 2 {
+            0 => x |= (value as u64) << (var / 2),
+            _ => y |= (value as u64) << (var / 2),
+        }
+    }
+    Ok(Some(Counterexample {
+        x,
+        y,
+        bit,
+        expected: reference.eval(x, y, z.len()),
+        got: machine.compile()?.add(x, y),
+    }))
+}
+
+impl Machine {
+    /// See [`check`]
+    pub fn check(&self, reference: Reference) -> Result<Option<Counterexample>> {
+        check(self, reference)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Write;
+
+    use crate::{
+        parse::parse,
+        test_util::{adder, machine},
+    };
+
+    use super::*;
+
+    #[test]
+    fn canonical() {
+        let mut bdd = Bdd::default();
+        let (a, b) = (bdd.var(0), bdd.var(1));
+
+        // a ^ b == (a | b) & !(a & b)
+        let xor = bdd.xor(a, b);
+        let or = bdd.or(a, b);
+        let and = bdd.and(a, b);
+        let nand = bdd.not(and);
+        assert_eq!(bdd.and(or, nand), xor);
+        assert_eq!(bdd.xor(xor, xor), Bdd::FALSE);
+        let not_a = bdd.not(a);
+        assert_eq!(bdd.or(a, not_a), Bdd::TRUE);
+
+        for (va, vb) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(bdd.eval(xor, |var| [va, vb][var]), va ^ vb);
+        }
+        assert_eq!(bdd.any_sat(and), Some(vec![(0, true), (1, true)]));
+        assert_eq!(bdd.any_sat(Bdd::FALSE), None);
+        assert_eq!(bdd.any_sat(Bdd::TRUE), Some(vec![]));
+    }
+
+    /// Array multiplier: sum of the partial products `x * y[i] << i`
+    fn multiplier(bits: usize) -> Machine {
+        machine(bits, |builder| {
+            let mut sum = vec![None; 2 * bits];
+            for i in 0..bits {
+                let mut carry = None;
+                for (k, sum) in sum.iter_mut().enumerate() {
+                    let partial = (i..i + bits).contains(&k).then(|| {
+                        builder.gate(&format!("x{:02}", k - i), "AND", &format!("y{i:02}"))
+                    });
+                    let bits = [sum.take(), partial, carry]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>();
+                    (*sum, carry) = builder.add(&bits);
+                }
+            }
+            for (n, wire) in sum.into_iter().enumerate() {
+                builder.output(n, wire);
+            }
+        })
+    }
+
+    fn check_counterexample(machine: &Machine, reference: Reference) -> Counterexample {
+        let counterexample = check(machine, reference).unwrap().unwrap();
+        let Counterexample {
+            x,
+            y,
+            bit,
+            expected,
+            got,
+        } = counterexample;
+        let bits = machine.o_bits as usize;
+        assert_eq!(expected, reference.eval(x, y, bits));
+        assert_eq!(got, machine.clone().add(x, y).2);
+        assert_eq!((expected ^ got).trailing_zeros() as usize, bit);
+        counterexample
+    }
+
+    #[test]
+    fn add() {
+        for alternate in [false, true] {
+            let machine = parse(&adder(45, alternate, &[])).unwrap();
+            assert_eq!(machine.check(Reference::Add).unwrap(), None);
+            check_counterexample(&machine, Reference::And);
+
+            let machine = parse(&adder(45, alternate, &[("s10", "a10"), ("z15", "c15")])).unwrap();
+            let counterexample = check_counterexample(&machine, Reference::Add);
+            assert_eq!(counterexample.bit, 10);
+        }
+    }
+
+    #[test]
+    fn and() {
+        let machine = machine(8, |builder| {
+            for n in 0..8 {
+                writeln!(builder.text, "x{n:02} AND y{n:02} -> z{n:02}").unwrap();
+            }
+        });
+        assert_eq!(machine.check(Reference::And).unwrap(), None);
+        check_counterexample(&machine, Reference::Add);
+        check_counterexample(&machine, Reference::Mul);
+    }
+
+    #[test]
+    fn mul() {
+        let mut machine = multiplier(5);
+        assert_eq!(machine.o_bits, 10);
+        assert_eq!(machine.add(27, 19).2, 27 * 19);
+        assert_eq!(machine.check(Reference::Mul).unwrap(), None);
+        check_counterexample(&machine, Reference::Add);
+
+        // Swap two outputs in the middle of the array
+        machine.swap_outputs(Wire::get_z(3), Wire::get_z(4));
+        let counterexample = check_counterexample(&machine, Reference::Mul);
+        assert_eq!(counterexample.bit, 3);
+    }
+}
@@ -0,0 +1,335 @@
+//! Compiled simulation of a [`Machine`].
+//!
+//! The gates are sorted topologically once, so that each one is evaluated a
+//! single time, after its inputs, with all the wire values in a dense array.
+//! Values are `u64`s: each bit is an independent lane, so 64 input vectors
+//! can be evaluated at once (bit-slicing). The scalar methods only use the
+//! lowest lane.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use itertools::Itertools;
+use miette::{miette, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+
+use crate::machine::{mask, Machine, Operator, Wire};
+
+/// Number of input vectors evaluated at once by [`Compiled::add_lanes`]
+pub const LANES: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct Compiled {
+    /// Wire names, indexed by wire
+    names: Vec<Wire>,
+    /// Gates in topological order: operator, inputs and output
+    gates: Vec<(Operator, usize, usize, usize)>,
+    /// Initial values of the machine
+    init: Vec<(usize, bool)>,
+    x: Vec<usize>,
+    y: Vec<usize>,
+    z: Vec<usize>,
+}
+
+impl Compiled {
+    /// Fails if the circuit contains a loop, if a wire is driven by several
+    /// gates, or if a wire is neither an initial value nor a gate output.
+    pub fn new(machine: &Machine) -> Result<Self> {
+        let mut names = Vec::new();
+        let mut ids = FxHashMap::default();
+        let mut id = |wire: Wire| {
+            *ids.entry(wire).or_insert_with(|| {
+                names.push(wire);
+                names.len() - 1
+            })
+        };
+
+        let x = (0..machine.i_bits).map(|n| id(Wire::get_x(n))).collect();
+        let y = (0..machine.i_bits).map(|n| id(Wire::get_y(n))).collect();
+        let z = (0..machine.o_bits).map(|n| id(Wire::get_z(n))).collect();
+        let init = machine
+            .init
+            .iter()
+            .map(|(&wire, &value)| (id(wire), value))
+            .sorted()
+            .collect::<Vec<_>>();
+        let gates = machine
+            .gates
+            .iter()
+            .map(|gate| (gate.operator, id(gate.in1), id(gate.in2), id(gate.out)))
+            .collect::<Vec<_>>();
+
+        let mut drivers = vec![None; names.len()];
+        for (idx, &(_, _, _, out)) in gates.iter().enumerate() {
+            if drivers[out].replace(idx).is_some() {
+                return Err(miette!("wire {} has several drivers", names[out]));
+            }
+        }
+        let mut set = vec![false; names.len()];
+        for &(wire, _) in &init {
+            set[wire] = true;
+        }
+        if let Some(wire) = (0..names.len()).find(|&wire| !set[wire] && drivers[wire].is_none()) {
+            return Err(miette!("wire {} is never set", names[wire]));
+        }
+
+        // Kahn's algorithm: a gate is ready once all its inputs driven by
+        // other gates have been evaluated. The ready gates are taken in the
+        // order of the machine, so that the order only changes where needed.
+        let mut missing = vec![0; gates.len()];
+        let mut consumers = vec![vec![]; names.len()];
+        for (idx, &(_, in1, in2, _)) in gates.iter().enumerate() {
+            for input in [in1, in2] {
+                if drivers[input].is_some() {
+                    missing[idx] += 1;
+                    consumers[input].push(idx);
+                }
+            }
+        }
+        let mut ready = (0..gates.len())
+            .filter(|&idx| missing[idx] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(gates.len());
+        while let Some(Reverse(idx)) = ready.pop() {
+            order.push(gates[idx]);
+            for &consumer in &consumers[gates[idx].3] {
+                missing[consumer] -= 1;
+                if missing[consumer] == 0 {
+                    ready.push(Reverse(consumer));
+                }
+            }
+        }
+        if order.len() < gates.len() {
+            // Drop the gates only blocked by the loop, i.e. whose output is
+            // not used by any other gate left
+            let mut left = (0..gates.len())
+                .map(|idx| missing[idx] > 0)
+                .collect::<Vec<_>>();
+            while let Some(idx) = (0..gates.len())
+                .find(|&idx| left[idx] && consumers[gates[idx].3].iter().all(|&c| !left[c]))
+            {
+                left[idx] = false;
+            }
+            let wires = (0..gates.len())
+                .filter(|&idx| left[idx])
+                .map(|idx| names[gates[idx].3])
+                .sorted()
+                .join(", ");
+            return Err(miette!("loop in the circuit, through wires {wires}"));
+        }
+
+        Ok(Self {
+            names,
+            gates: order,
+            init,
+            x,
+            y,
+            z,
+        })
+    }
+
+    /// Gate outputs, in evaluation order
+    pub fn order(&self) -> impl Iterator<Item = Wire> + '_ {
+        self.gates.iter().map(|&(_, _, _, out)| self.names[out])
+    }
+
+    /// Operator, inputs and output of the gates, in evaluation order
+    pub fn gates(&self) -> impl Iterator<Item = (Operator, Wire, Wire, Wire)> + '_ {
+        self.gates.iter().map(|&(operator, in1, in2, out)| {
+            (operator, self.names[in1], self.names[in2], self.names[out])
+        })
+    }
+
+    /// Same as [`Machine::execute`]
+    pub fn execute(&self) -> u64 {
+        let values = self.evaluate(None);
+        self.z
+            .iter()
+            .rev()
+            .fold(0, |acc, &z| acc << 1 | (values[z] & 1))
+    }
+
+    /// `z` computed from `x` and `y`, same as [`Machine::add`]. The other
+    /// initial values of the machine are kept.
+    pub fn add(&self, x: u64, y: u64) -> u64 {
+        let bits = |value: u64| {
+            (0..self.x.len())
+                .map(|n| value >> n & 1)
+                .collect::<Vec<_>>()
+        };
+        let values = self.evaluate(Some((&bits(x), &bits(y))));
+        self.z
+            .iter()
+            .rev()
+            .fold(0, |acc, &z| acc << 1 | (values[z] & 1))
+    }
+
+    /// Same as [`Compiled::add`] for each of the [`LANES`] pairs of inputs
+    pub fn add_lanes(&self, x: &[u64; LANES], y: &[u64; LANES]) -> [u64; LANES] {
+        let bits = |values| {
+            (0..self.x.len())
+                .map(|n| transpose(values, n))
+                .collect::<Vec<_>>()
+        };
+        let values = self.evaluate(Some((&bits(x), &bits(y))));
+        let mut z = [0; LANES];
+        // Like `add`, the bits above 64 are dropped
+        for (n, &wire) in self.z.iter().take(u64::BITS as usize).enumerate() {
+            for (lane, z) in z.iter_mut().enumerate() {
+                *z |= (values[wire] >> lane & 1) << n;
+            }
+        }
+        z
+    }
+
+    /// Check that the circuit adds `rounds * LANES` random pairs of inputs
+    /// correctly, returning the first wrong addition as `(x, y, z)`
+    pub fn check_random(&self, rounds: usize, seed: u64) -> Option<(u64, u64, u64)> {
+        let i_mask = mask(self.x.len());
+        let o_mask = mask(self.z.len());
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..rounds).find_map(|_| {
+            let x: [u64; LANES] = std::array::from_fn(|_| rng.gen::<u64>() & i_mask);
+            let y: [u64; LANES] = std::array::from_fn(|_| rng.gen::<u64>() & i_mask);
+            let z = self.add_lanes(&x, &y);
+            (0..LANES)
+                .find(|&lane| z[lane] != x[lane].wrapping_add(y[lane]) & o_mask)
+                .map(|lane| (x[lane], y[lane], z[lane]))
+        })
+    }
+
+    /// Values of all the wires, with the lanes of each input bit replacing
+    /// the initial values if given
+    fn evaluate(&self, inputs: Option<(&[u64], &[u64])>) -> Vec<u64> {
+        let mut values = vec![0; self.names.len()];
+        for &(wire, value) in &self.init {
+            values[wire] = if value { u64::MAX } else { 0 };
+        }
+        if let Some((x, y)) = inputs {
+            for (n, (&xn, &yn)) in self.x.iter().zip(&self.y).enumerate() {
+                values[xn] = x[n];
+                values[yn] = y[n];
+            }
+        }
+        for &(operator, in1, in2, out) in &self.gates {
+            values[out] = match operator {
+                Operator::And => values[in1] & values[in2],
+                Operator::Or => values[in1] | values[in2],
+                Operator::Xor => values[in1] ^ values[in2],
+                Operator::Not => !values[in1],
+            };
+        }
+        values
+    }
+}
+
+/// Bit `n` of each of the values, as the lanes of a single value
+fn transpose(values: &[u64; LANES], n: usize) -> u64 {
+    values
+        .iter()
+        .enumerate()
+        .fold(0, |acc, (lane, value)| acc | (value >> n & 1) << lane)
+}
+
+impl Machine {
+    pub fn compile(&self) -> Result<Compiled> {
+        Compiled::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse::parse, test_util::adder};
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02
+"#;
+    // cspell:enable
+
+    #[test]
+    fn execute() {
+        let mut machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        let compiled = machine.compile().unwrap();
+        assert_eq!(compiled.execute(), 4);
+        assert_eq!(compiled.execute(), machine.execute());
+    }
+
+    #[test]
+    fn topological_order() {
+        let machine = parse(&adder(8, true, &[])).unwrap();
+        let compiled = machine.compile().unwrap();
+        let position = compiled
+            .order()
+            .enumerate()
+            .map(|(idx, wire)| (wire, idx))
+            .collect::<FxHashMap<_, _>>();
+        assert_eq!(position.len(), machine.gates.len());
+        for gate in &machine.gates {
+            for input in [gate.in1, gate.in2] {
+                if let Some(&before) = position.get(&input) {
+                    assert!(before < position[&gate.out], "{input} -> {}", gate.out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn add() {
+        let mut machine = parse(&adder(20, false, &[("z05", "b05")])).unwrap();
+        let compiled = machine.compile().unwrap();
+
+        let mut rng = StdRng::seed_from_u64(39);
+        let x: [u64; LANES] = std::array::from_fn(|_| rng.gen_range(0..1 << 20));
+        let y: [u64; LANES] = std::array::from_fn(|_| rng.gen_range(0..1 << 20));
+        let z = compiled.add_lanes(&x, &y);
+        for lane in 0..LANES {
+            let (_, _, expected) = machine.add(x[lane], y[lane]);
+            assert_eq!(compiled.add(x[lane], y[lane]), expected);
+            assert_eq!(z[lane], expected);
+        }
+    }
+
+    #[test]
+    fn check_random() {
+        for bits in [45, 63, 64] {
+            let machine = parse(&adder(bits, false, &[])).unwrap();
+            assert_eq!(machine.compile().unwrap().check_random(100, 0), None);
+        }
+
+        let machine = parse(&adder(45, true, &[("s30", "a30")])).unwrap();
+        let (x, y, z) = machine.compile().unwrap().check_random(100, 0).unwrap();
+        assert_ne!(x + y, z);
+    }
+
+    #[test]
+    fn invalid() {
+        // s05 depends on b05, which depends on s05
+        let machine = parse(&adder(8, false, &[("s05", "c05")])).unwrap();
+        let err = machine.compile().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "loop in the circuit, through wires b05, s05"
+        );
+
+        let mut machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        machine.gates[1].out = Wire::get_z(0);
+        assert!(machine.compile().is_err());
+
+        let mut machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        machine.init.remove(&Wire::get_y(1));
+        assert!(machine.compile().is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Export of a [`Machine`] to other formats, to inspect it with external
+//! tools: Graphviz DOT for the gate graph, and a structural Verilog netlist
+//! for simulators. The Verilog netlist keeps the initial values, so that it
+//! can be imported back with [`parse_verilog`](crate::parse::parse_verilog).
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+use rustc_hash::FxHashSet;
+
+use crate::machine::{Gate, Machine, Operator, Wire};
+
+/// Gate graph in DOT: a node per wire, labeled with the operator of the gate
+/// driving it. The X/Y inputs are ranked first, the Z outputs last, and the
+/// `highlight` wires are filled in red, with their outgoing edges.
+pub fn to_dot(machine: &Machine, highlight: &[Wire]) -> String {
+    let highlight = highlight.iter().collect::<FxHashSet<_>>();
+    let style = |wire: &Wire| match highlight.contains(wire) {
+        true => ", style=filled, fillcolor=red",
+        false => "",
+    };
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph circuit {{").unwrap();
+    writeln!(dot, "    rankdir=LR;").unwrap();
+    writeln!(dot, "    node [shape=box];").unwrap();
+
+    let inputs = machine.init.keys().sorted().collect::<Vec<_>>();
+    writeln!(dot, "    {{").unwrap();
+    writeln!(dot, "        rank=source;").unwrap();
+    for wire in &inputs {
+        writeln!(dot, "        \"{wire}\" [shape=ellipse{}];", style(wire)).unwrap();
+    }
+    writeln!(dot, "    }}").unwrap();
+
+    let (outputs, others): (Vec<&Gate>, Vec<_>) = machine
+        .gates
+        .iter()
+        .sorted_by_key(|gate| gate.out)
+        .partition(|gate| matches!(gate.out.io_bit(), Some((b'z', _))));
+    writeln!(dot, "    {{").unwrap();
+    writeln!(dot, "        rank=sink;").unwrap();
+    for gate in &outputs {
+        let (operator, out) = (gate.operator, gate.out);
+        writeln!(
+            dot,
+            "        \"{out}\" [label=\"{operator}\\n{out}\"{}];",
+            style(&out)
+        )
+        .unwrap();
+    }
+    writeln!(dot, "    }}").unwrap();
+    for gate in &others {
+        let (operator, out) = (gate.operator, gate.out);
+        writeln!(
+            dot,
+            "    \"{out}\" [label=\"{operator}\\n{out}\"{}];",
+            style(&out)
+        )
+        .unwrap();
+    }
+
+    for gate in machine.gates.iter().sorted_by_key(|gate| gate.out) {
+        let inputs = match gate.operator {
+            Operator::Not => vec![gate.in1],
+            _ => vec![gate.in1, gate.in2],
+        };
+        for input in inputs {
+            let color = match highlight.contains(&input) {
+                true => " [color=red]",
+                false => "",
+            };
+            writeln!(dot, "    \"{input}\" -> \"{}\"{color};", gate.out).unwrap();
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+/// Structural Verilog module with `x`/`y` input and `z` output buses, and a
+/// gate primitive per gate. The initial values of `x` and `y` are kept as
+/// the `X_INIT`/`Y_INIT` parameters, the other ones (including constant `z`
+/// bits) become constant assignments.
+pub fn to_verilog(machine: &Machine, module: &str) -> String {
+    let in_bits = machine.i_bits.max(1);
+    let out_bits = machine
+        .gates
+        .iter()
+        .map(|gate| &gate.out)
+        .chain(machine.init.keys())
+        .filter_map(|wire| match wire.io_bit() {
+            Some((b'z', n)) => Some(n + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(1);
+    let name = |wire: Wire| match wire.io_bit() {
+        Some((letter, n)) => format!("{}[{n}]", letter as char),
+        None => format!("w_{wire}"),
+    };
+
+    let mut verilog = String::new();
+    writeln!(verilog, "module {module}(x, y, z);").unwrap();
+    writeln!(verilog, "    input [{}:0] x;", in_bits - 1).unwrap();
+    writeln!(verilog, "    input [{}:0] y;", in_bits - 1).unwrap();
+    writeln!(verilog, "    output [{}:0] z;", out_bits - 1).unwrap();
+    for (param, base) in [("X_INIT", Wire::X_START), ("Y_INIT", Wire::Y_START)] {
+        let bits = (0..in_bits)
+            .rev()
+            .map(|n| match machine.init.get(&Wire::get_io(base, n)) {
+                Some(true) => '1',
+                _ => '0',
+            })
+            .collect::<String>();
+        writeln!(
+            verilog,
+            "    localparam [{}:0] {param} = {in_bits}'b{bits};",
+            in_bits - 1
+        )
+        .unwrap();
+    }
+
+    let mut internal = machine
+        .init
+        .keys()
+        .chain(machine.gates.iter().map(|gate| &gate.out))
+        .filter(|wire| wire.io_bit().is_none())
+        .sorted()
+        .dedup()
+        .peekable();
+    if internal.peek().is_some() {
+        writeln!(verilog).unwrap();
+    }
+    for wire in internal {
+        writeln!(verilog, "    wire {};", name(*wire)).unwrap();
+    }
+
+    for (&wire, &value) in machine.init.iter().sorted() {
+        if !matches!(wire.io_bit(), Some((b'x' | b'y', _))) {
+            writeln!(verilog, "    assign {} = 1'b{};", name(wire), value as u8).unwrap();
+        }
+    }
+
+    writeln!(verilog).unwrap();
+    for (idx, gate) in machine.gates.iter().enumerate() {
+        let primitive = gate.operator.to_string().to_lowercase();
+        let (out, in1, in2) = (name(gate.out), name(gate.in1), name(gate.in2));
+        match gate.operator {
+            Operator::Not => writeln!(verilog, "    {primitive} g{idx} ({out}, {in1});"),
+            _ => writeln!(verilog, "    {primitive} g{idx} ({out}, {in1}, {in2});"),
+        }
+        .unwrap();
+    }
+    writeln!(verilog, "endmodule").unwrap();
+    verilog
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        parse::{parse, parse_verilog},
+        test_util::adder,
+    };
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+x00: 1
+x01: 0
+y00: 1
+y01: 1
+
+x00 XOR y00 -> z00
+x00 AND y00 -> c00
+x01 XOR y01 -> s01
+s01 XOR c00 -> z01
+"#;
+    // cspell:enable
+
+    #[test]
+    fn dot() {
+        let machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        let dot = to_dot(&machine, &[Wire::get_x(1), Wire::get_z(0)]);
+        assert_eq!(
+            dot,
+            r#"digraph circuit {
+    rankdir=LR;
+    node [shape=box];
+    {
+        rank=source;
+        "x00" [shape=ellipse];
+        "x01" [shape=ellipse, style=filled, fillcolor=red];
+        "y00" [shape=ellipse];
+        "y01" [shape=ellipse];
+    }
+    {
+        rank=sink;
+        "z00" [label="XOR\nz00", style=filled, fillcolor=red];
+        "z01" [label="XOR\nz01"];
+    }
+    "c00" [label="AND\nc00"];
+    "s01" [label="XOR\ns01"];
+    "x00" -> "c00";
+    "y00" -> "c00";
+    "x01" -> "s01" [color=red];
+    "y01" -> "s01";
+    "x00" -> "z00";
+    "y00" -> "z00";
+    "s01" -> "z01";
+    "c00" -> "z01";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn verilog() {
+        let machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        assert_eq!(
+            to_verilog(&machine, "sample"),
+            r#"module sample(x, y, z);
+    input [1:0] x;
+    input [1:0] y;
+    output [1:0] z;
+    localparam [1:0] X_INIT = 2'b01;
+    localparam [1:0] Y_INIT = 2'b11;
+
+    wire w_c00;
+    wire w_s01;
+
+    xor g0 (z[0], x[0], y[0]);
+    and g1 (w_c00, x[0], y[0]);
+    xor g2 (w_s01, x[1], y[1]);
+    xor g3 (z[1], w_s01, w_c00);
+endmodule
+"#
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = adder(45, true, &[("z05", "b05"), ("s10", "a10")]);
+        let machine = parse(&text).unwrap();
+        assert_eq!(machine.to_string().as_bytes(), text);
+
+        let imported = parse_verilog(to_verilog(&machine, "adder").as_bytes()).unwrap();
+        assert_eq!(imported.to_string().as_bytes(), text);
+        assert_eq!(imported.i_bits, machine.i_bits);
+        assert_eq!(imported.o_bits, machine.o_bits);
+
+        // Constants and NOT gates
+        let mut machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        let one = Wire::new(1);
+        machine.init.insert(one, true);
+        machine.gates[1].operator = Operator::Not;
+        machine.gates[1].in2 = machine.gates[1].in1;
+        machine.gates[2].in2 = one;
+        let verilog = to_verilog(&machine, "sample");
+        assert!(verilog.contains("    wire w_001;\n"));
+        assert!(verilog.contains("    assign w_001 = 1'b1;\n"));
+        assert!(verilog.contains("    not g1 (w_c00, x[0]);\n"));
+        let imported = parse_verilog(verilog.as_bytes()).unwrap();
+        assert_eq!(imported.to_string(), machine.to_string());
+        assert_eq!(imported.gates, machine.gates);
+    }
+}
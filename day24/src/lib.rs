@@ -1,4 +1,12 @@
+pub mod bdd;
+pub mod compiled;
+pub mod export;
 pub mod machine;
 pub mod parse;
 pub mod part1;
 pub mod part2;
+pub mod part2_rules;
+pub mod repair;
+pub mod simplify;
+#[cfg(test)]
+mod test_util;
@@ -3,7 +3,9 @@ use std::fmt::Debug;
 use std::fmt::Display;
 use std::mem::swap;
 use std::num::NonZeroU16;
+use std::str::FromStr;
 
+use itertools::Itertools;
 use rustc_hash::FxHashMap;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -42,6 +44,15 @@ impl Wire {
             .then_some(self.0.get() & Self::IO_MASK)
     }
 
+    /// Letter (`x`, `y` or `z`) and bit number of an input or output wire,
+    /// e.g. `(b'z', 12)` for `z12`
+    pub fn io_bit(&self) -> Option<(u8, u16)> {
+        let w = self.0.get();
+        let letter = (w / (36 * 36)).checked_sub(10)? as u8 + b'a';
+        let (d2, d1) = ((w / 36) % 36, w % 36);
+        (matches!(letter, b'x'..=b'z') && d2 < 10 && d1 < 10).then_some((letter, d2 * 10 + d1))
+    }
+
     pub fn is_output(&self) -> Option<u16> {
         (Self::Z_START.0..)
             .contains(&self.0)
@@ -61,6 +72,20 @@ impl Display for Wire {
     }
 }
 
+impl FromStr for Wire {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digit = |c: char| c.to_digit(36).filter(|_| !c.is_ascii_uppercase());
+        match s.chars().map(digit).collect::<Option<Vec<_>>>().as_deref() {
+            Some(&[d3, d2, d1]) if (d3, d2, d1) != (0, 0, 0) => {
+                Ok(Self::new((d3 * 36 * 36 + d2 * 36 + d1) as u16))
+            }
+            _ => Err(miette::miette!("invalid wire name '{s}'")),
+        }
+    }
+}
+
 impl Debug for Wire {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self)
@@ -75,6 +100,18 @@ pub enum Operator {
     Not,
 }
 
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Operator::And => "AND",
+            Operator::Or => "OR",
+            Operator::Xor => "XOR",
+            Operator::Not => "NOT",
+        };
+        write!(f, "{name}")
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Gate {
     pub operator: Operator,
@@ -103,6 +140,27 @@ pub struct Machine {
     pub o_bits: u16, // for part 2, should be i_bits+1, but not true in part 1
 }
 
+/// The machine in the input format, with the initial values sorted
+impl Display for Machine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for wire in self.init.keys().sorted() {
+            writeln!(f, "{wire}: {}", self.init[wire] as u8)?;
+        }
+        writeln!(f)?;
+        for gate in &self.gates {
+            match gate.operator {
+                Operator::Not => writeln!(f, "NOT {} -> {}", gate.in1, gate.out)?,
+                _ => writeln!(
+                    f,
+                    "{} {} {} -> {}",
+                    gate.in1, gate.operator, gate.in2, gate.out
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Machine {
     pub fn new(init: FxHashMap<Wire, bool>, gates: Vec<Gate>) -> Self {
         // Validate the input
@@ -114,9 +172,11 @@ impl Machine {
             .keys()
             .filter(|wire| (Wire::Y_START..Wire::Z_START).contains(wire))
             .count() as u16;
+        // Z wires can also be constants
         let z_bits = gates
             .iter()
             .map(|gate| gate.out)
+            .chain(init.keys().copied())
             .filter(|wire| *wire >= Wire::Z_START)
             .count() as u16;
         assert_eq!(
@@ -158,8 +218,36 @@ impl Machine {
     }
 
     pub fn add(&mut self, x: u64, y: u64) -> (u64, u64, u64) {
-        // Set init
-        self.init.clear();
+        let (masked_x, masked_y) = self.set_inputs(x, y);
+        while self.tick() {}
+        (masked_x, masked_y, self.get_output())
+    }
+
+    /// Same as [`Machine::add`], but returns `None` instead of panicking if
+    /// an output wire is never set, or instead of running forever if the
+    /// circuit is still changing after one tick per gate (e.g. because of a
+    /// loop in the circuit)
+    pub fn checked_add(&mut self, x: u64, y: u64) -> Option<(u64, u64, u64)> {
+        let (masked_x, masked_y) = self.set_inputs(x, y);
+        // Without a loop, every tick settles at least one more gate
+        let mut ticks = 0;
+        while self.tick() {
+            ticks += 1;
+            if ticks > self.gates.len() {
+                return None;
+            }
+        }
+        Some((masked_x, masked_y, self.try_get_output()?))
+    }
+
+    /// Value of `z[bit]` after the last addition, `None` if it was never set.
+    /// Useful for the bits not fitting in the `u64` output.
+    pub fn output_bit(&self, bit: u16) -> Option<bool> {
+        self.outputs.get(&Wire::get_z(bit)).copied()
+    }
+
+    fn set_inputs(&mut self, x: u64, y: u64) -> (u64, u64) {
+        // Set init, the initial values of the other wires are constants
         let masked_x = self.extend_init(Wire::X_START, x, self.i_bits);
         let masked_y = self.extend_init(Wire::Y_START, y, self.i_bits);
 
@@ -170,9 +258,24 @@ impl Machine {
             g.last_val = None;
         });
 
-        while self.tick() {}
+        (masked_x, masked_y)
+    }
 
-        (masked_x, masked_y, self.get_output())
+    /// Swap the output wires of the gates driving `w1` and `w2`
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the wires is not the output of a gate
+    pub fn swap_outputs(&mut self, w1: Wire, w2: Wire) {
+        let find = |wire: Wire| {
+            self.gates
+                .iter()
+                .position(|gate| gate.out == wire)
+                .unwrap_or_else(|| panic!("no gate drives {wire}"))
+        };
+        let (g1, g2) = (find(w1), find(w2));
+        self.gates[g1].out = w2;
+        self.gates[g2].out = w1;
     }
 
     pub fn execute(&mut self) -> u64 {
@@ -228,10 +331,17 @@ impl Machine {
     fn extend_init(&mut self, base_wire: Wire, value: u64, max: u16) -> u64 {
         (0..max).for_each(|n| {
             let wire = Wire::new(base_wire.0.get() + (n / 10) * 36 + (n % 10));
-            let wire_val = (value >> max & 1) == 1;
+            let wire_val = (value >> n & 1) == 1;
             self.init.insert(wire, wire_val);
         });
-        value & ((1 << max) - 1)
+        value & mask(max as usize)
+    }
+
+    fn try_get_output(&self) -> Option<u64> {
+        (0..self.o_bits).rev().try_fold(0, |acc, bit| {
+            let value = *self.outputs.get(&Wire::get_z(bit))?;
+            Some(acc << 1 | value as u64)
+        })
     }
 
     fn get_output(&self) -> u64 {
@@ -255,3 +365,11 @@ impl Machine {
         //     .fold(0, |acc, (_, v)| acc << 1 | if v { 1 } else { 0 })
     }
 }
+
+/// Mask of the `bits` lowest bits
+pub(crate) fn mask(bits: usize) -> u64 {
+    match bits {
+        64.. => u64::MAX,
+        _ => (1 << bits) - 1,
+    }
+}
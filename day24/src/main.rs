@@ -1,8 +1,14 @@
 use clap::{Parser, Subcommand};
 use common::read_input_u8;
-use miette::Result;
+use day24::{
+    bdd::{self, Reference},
+    export,
+    machine::Wire,
+    parse::{parse, parse_verilog},
+    part1, part2, part2_rules, repair, simplify,
+};
+use miette::{IntoDiagnostic, Result};
 use std::path::PathBuf;
-use day24::{part1, part2};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -18,6 +24,76 @@ enum Command {
 
     /// Part 2
     Part2 { file: Option<PathBuf> },
+
+    /// Part 2, with rules on the half-adders (incomplete)
+    Part2Rules { file: Option<PathBuf> },
+
+    /// Check the circuit against a reference function, for all the inputs
+    Check {
+        file: Option<PathBuf>,
+
+        #[arg(short, long, value_enum, default_value_t = Reference::Add)]
+        reference: Reference,
+    },
+
+    /// Simplify the circuit, and report what was removed
+    Simplify {
+        file: Option<PathBuf>,
+
+        /// Inputs whose initial value is a constant, e.g. `x00,y05`
+        #[arg(long, value_delimiter = ',')]
+        fix: Vec<Wire>,
+
+        /// File to write the simplified input to (stdout by default)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export the gate graph in Graphviz DOT
+    Dot {
+        file: Option<PathBuf>,
+
+        /// Highlight the wires swapped by the repair of the adder
+        #[arg(long)]
+        highlight: bool,
+
+        /// File to write the graph to (stdout by default)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export the circuit as a structural Verilog netlist
+    Verilog {
+        file: Option<PathBuf>,
+
+        /// Name of the Verilog module
+        #[arg(short, long, default_value = "adder")]
+        module: String,
+
+        /// File to write the netlist to (stdout by default)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Convert a Verilog netlist exported by `verilog` back to the input
+    /// format
+    Import {
+        file: PathBuf,
+
+        /// File to write the input to (stdout by default)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn write_output(output: Option<PathBuf>, content: &str) -> Result<()> {
+    match output {
+        Some(output) => std::fs::write(output, content).into_diagnostic(),
+        None => {
+            print!("{content}");
+            Ok(())
+        }
+    }
 }
 
 fn main() -> Result<()> {
@@ -30,6 +106,50 @@ fn main() -> Result<()> {
         Some(Command::Part2 { file }) => {
             println!("Result: {}", part2::run(&read_input_u8!(file)?)?)
         }
+        Some(Command::Part2Rules { file }) => {
+            println!("Result: {}", part2_rules::run(&read_input_u8!(file)?)?)
+        }
+        Some(Command::Check { file, reference }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            match bdd::check(&machine, reference)? {
+                None => println!("The circuit computes z = {reference}"),
+                Some(counterexample) => println!("Counterexample: {counterexample}"),
+            }
+        }
+        Some(Command::Simplify { file, fix, output }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            let (simplified, report) = simplify::simplify(&machine, &fix)?;
+            write_output(output, &simplified.to_string())?;
+            eprint!("{report}");
+        }
+        Some(Command::Dot {
+            file,
+            highlight,
+            output,
+        }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            let highlight = match highlight {
+                true => repair::repair(&machine, 4)?
+                    .swaps
+                    .into_iter()
+                    .flat_map(|(w1, w2)| [w1, w2])
+                    .collect(),
+                false => vec![],
+            };
+            write_output(output, &export::to_dot(&machine, &highlight))?;
+        }
+        Some(Command::Verilog {
+            file,
+            module,
+            output,
+        }) => {
+            let machine = parse(&read_input_u8!(file)?)?;
+            write_output(output, &export::to_verilog(&machine, &module))?;
+        }
+        Some(Command::Import { file, output }) => {
+            let machine = parse_verilog(&std::fs::read(file).into_diagnostic()?)?;
+            write_output(output, &machine.to_string())?;
+        }
         None => println!("Result: {}", part1::run(&read_input_u8!(None)?)?),
     }
 
@@ -1,10 +1,10 @@
 use common::error::AdventError;
 use rustc_hash::FxHashMap;
 use winnow::{
-    ascii::{line_ending, space1},
-    combinator::{alt, repeat, separated_pair, terminated, trace},
+    ascii::{dec_uint, line_ending, multispace0, space0, space1, till_line_ending},
+    combinator::{alt, delimited, opt, preceded, repeat, separated_pair, terminated, trace},
     prelude::*,
-    token::one_of,
+    token::{one_of, take_till, take_while},
 };
 
 use crate::machine::{Gate, Machine, Operator, Wire};
@@ -41,8 +41,33 @@ fn parse_gates(input: &mut &[u8]) -> PResult<Vec<Gate>> {
 }
 
 fn parse_gate(input: &mut &[u8]) -> PResult<Gate> {
+    trace("parse_gate", alt((parse_not_gate, parse_binary_gate))).parse_next(input)
+}
+
+/// `NOT in -> out`, with both inputs set to `in`
+fn parse_not_gate(input: &mut &[u8]) -> PResult<Gate> {
+    trace(
+        "parse_not_gate",
+        (
+            (b"NOT", space1),
+            parse_wire_name,
+            (space1, b"->", space1),
+            parse_wire_name,
+        )
+            .map(|(_, in1, _, out)| Gate {
+                operator: Operator::Not,
+                in1,
+                in2: in1,
+                out,
+                last_val: None,
+            }),
+    )
+    .parse_next(input)
+}
+
+fn parse_binary_gate(input: &mut &[u8]) -> PResult<Gate> {
     trace(
-        "parse_gate",
+        "parse_binary_gate",
         (
             parse_wire_name,
             space1,
@@ -90,3 +115,153 @@ fn parse_wire_digit(input: &mut &[u8]) -> PResult<u16> {
 fn parse_bool(input: &mut &[u8]) -> PResult<bool> {
     trace("parse_bool", alt(("1".value(true), "0".value(false)))).parse_next(input)
 }
+
+/// Statement of a Verilog netlist exported by
+/// [`to_verilog`](crate::export::to_verilog)
+#[derive(Debug, Clone)]
+enum Statement {
+    /// Declarations, comments, ... that don't change the machine
+    Ignored,
+    /// `X_INIT`/`Y_INIT` parameter, as the base wire and the bits (most
+    /// significant first)
+    Init(Wire, Vec<bool>),
+    /// Constant assignment
+    Const(Wire, bool),
+    Gate(Gate),
+}
+
+/// Import a structural Verilog netlist exported by
+/// [`to_verilog`](crate::export::to_verilog)
+pub fn parse_verilog(content: &[u8]) -> Result<Machine, AdventError> {
+    Ok(trace(
+        "parse_verilog",
+        repeat(0.., delimited(multispace0, parse_statement, multispace0)).map(
+            |statements: Vec<Statement>| {
+                let mut init = FxHashMap::default();
+                let mut gates = Vec::new();
+                for statement in statements {
+                    match statement {
+                        Statement::Ignored => {}
+                        Statement::Init(base, bits) => {
+                            for (n, &value) in bits.iter().rev().enumerate() {
+                                init.insert(Wire::get_io(base, n as u16), value);
+                            }
+                        }
+                        Statement::Const(wire, value) => {
+                            init.insert(wire, value);
+                        }
+                        Statement::Gate(gate) => gates.push(gate),
+                    }
+                }
+                Machine::new(init, gates)
+            },
+        ),
+    )
+    .parse(content)?)
+}
+
+fn parse_statement(input: &mut &[u8]) -> PResult<Statement> {
+    trace(
+        "parse_statement",
+        alt((
+            (b"//", till_line_ending).value(Statement::Ignored),
+            b"endmodule".value(Statement::Ignored),
+            (
+                alt((b"module", b"input", b"output", b"wire")),
+                space1,
+                take_till(0.., b';'),
+                b';',
+            )
+                .value(Statement::Ignored),
+            parse_init,
+            (
+                b"assign",
+                space1,
+                parse_verilog_wire,
+                (space0, b'=', space0, b"1'b"),
+                parse_bool,
+                b';',
+            )
+                .map(|(_, _, wire, _, value, _)| Statement::Const(wire, value)),
+            parse_primitive.map(Statement::Gate),
+        )),
+    )
+    .parse_next(input)
+}
+
+fn parse_init(input: &mut &[u8]) -> PResult<Statement> {
+    trace(
+        "parse_init",
+        (
+            (
+                b"localparam",
+                space1,
+                b'[',
+                dec_uint::<_, u16, _>,
+                b":0]",
+                space1,
+            ),
+            alt((
+                b"X_INIT".value(Wire::X_START),
+                b"Y_INIT".value(Wire::Y_START),
+            )),
+            (space0, b'=', space0, dec_uint::<_, u16, _>, b"'b"),
+            repeat(1.., parse_bool),
+            b';',
+        )
+            .map(|(_, base, _, bits, _)| Statement::Init(base, bits)),
+    )
+    .parse_next(input)
+}
+
+fn parse_primitive(input: &mut &[u8]) -> PResult<Gate> {
+    trace(
+        "parse_primitive",
+        (
+            alt((
+                b"and".value(Operator::And),
+                b"xor".value(Operator::Xor),
+                b"or".value(Operator::Or),
+                b"not".value(Operator::Not),
+            )),
+            (
+                space1,
+                take_while(1.., |b: u8| b.is_ascii_alphanumeric() || b == b'_'),
+            ),
+            (space0, b'(', space0),
+            parse_verilog_wire,
+            (space0, b',', space0),
+            parse_verilog_wire,
+            opt(preceded((space0, b',', space0), parse_verilog_wire)),
+            (space0, b')', space0, b';'),
+        )
+            .map(|(operator, _, _, out, _, in1, in2, _)| Gate {
+                operator,
+                in1,
+                in2: in2.unwrap_or(in1),
+                out,
+                last_val: None,
+            }),
+    )
+    .parse_next(input)
+}
+
+/// `x[n]`, `y[n]`, `z[n]` or `w_` followed by a wire name
+fn parse_verilog_wire(input: &mut &[u8]) -> PResult<Wire> {
+    trace(
+        "parse_verilog_wire",
+        alt((
+            (
+                alt((
+                    b'x'.value(Wire::X_START),
+                    b'y'.value(Wire::Y_START),
+                    b'z'.value(Wire::Z_START),
+                )),
+                delimited(b'[', dec_uint::<_, u16, _>.verify(|&n| n < 100), b']'),
+            )
+                .map(|(base, n)| Wire::get_io(base, n)),
+            preceded(b"w_", parse_wire_name),
+        )),
+    )
+    .parse_next(input)
+}
@@ -0,0 +1,359 @@
+//! Repair of an adder circuit whose gate outputs have been swapped.
+//!
+//! Instead of relying on the structure of a given adder layout, the circuit
+//! is simulated: bits are checked from the least significant one, with all
+//! the combinations of the two input bits and of the incoming carry. At the
+//! first wrong bit, the gates around that bit are swapped with every other
+//! gate, keeping the swaps fixing all the bits so far, and backtracking if a
+//! later bit can't be fixed. This works for any ripple-carry layout, whatever
+//! order the half-adders combine X, Y and the carry in.
+//!
+//! The repaired circuit is then applied to a clone of the [`Machine`] and
+//! verified with [`Machine::checked_add`].
+
+use itertools::Itertools;
+use miette::{miette, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::machine::{mask, Machine, Operator, Wire};
+
+/// Number of random additions checked after the per-bit checks
+pub const RANDOM_CHECKS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct Repair {
+    /// Swapped output wires
+    pub swaps: Vec<(Wire, Wire)>,
+    /// The repaired machine
+    pub machine: Machine,
+}
+
+impl Repair {
+    /// All the swapped wires, sorted and joined with `,`
+    pub fn answer(&self) -> String {
+        self.swaps
+            .iter()
+            .flat_map(|&(w1, w2)| [w1, w2])
+            .sorted()
+            .join(",")
+    }
+}
+
+/// Find at most `max_swaps` swaps of gate outputs making the machine a
+/// correct adder, then verify the repaired machine.
+pub fn repair(machine: &Machine, max_swaps: usize) -> Result<Repair> {
+    let mut circuit = Circuit::new(machine)?;
+    let mut swaps = Vec::new();
+    if !circuit.search(0, &mut swaps, max_swaps) {
+        return Err(miette!(
+            "couldn't repair the adder with at most {max_swaps} swaps"
+        ));
+    }
+
+    let swaps = swaps
+        .into_iter()
+        .map(|(g1, g2)| (circuit.wire(g1), circuit.wire(g2)))
+        .collect::<Vec<_>>();
+    let mut repaired = machine.clone();
+    for &(w1, w2) in &swaps {
+        repaired.swap_outputs(w1, w2);
+    }
+    verify(&repaired, RANDOM_CHECKS)?;
+
+    Ok(Repair {
+        swaps,
+        machine: repaired,
+    })
+}
+
+/// Check that the machine adds correctly, with [`Machine::checked_add`]:
+/// every bit with all the combinations of input bits and incoming carry, then
+/// `random_checks` random additions. A loop in the circuit is reported as an
+/// error.
+pub fn verify(machine: &Machine, random_checks: usize) -> Result<()> {
+    let mut machine = machine.clone();
+    let bits = machine.i_bits as usize;
+    let (i_mask, o_mask) = (mask(bits), mask(bits + 1));
+
+    let mut rng = StdRng::seed_from_u64(24);
+    let random = (0..random_checks).map(|_| (rng.gen::<u64>() & i_mask, rng.gen::<u64>() & i_mask));
+
+    for (x, y) in (0..bits).flat_map(bit_checks).chain(random) {
+        let (sum, carry) = x.overflowing_add(y);
+        let expected = sum & o_mask;
+        let Some((_, _, z)) = machine.checked_add(x, y) else {
+            return Err(miette!("{x} + {y} doesn't settle, or misses an output"));
+        };
+        if z != expected {
+            return Err(miette!("{x} + {y} should be {expected}, got {z}"));
+        }
+        // The carry out of a 64-bit adder doesn't fit in the output
+        if bits == 64 && machine.output_bit(64) != Some(carry) {
+            return Err(miette!("{x} + {y} should have a carry out of {carry}"));
+        }
+    }
+    Ok(())
+}
+
+/// Additions checking bit `n`: all the combinations of `x[n]`, `y[n]`, and
+/// of a carry from bit `n - 1`
+fn bit_checks(n: usize) -> impl Iterator<Item = (u64, u64)> {
+    let carries = if n == 0 { 0..1 } else { 0..2 };
+    carries.flat_map(move |carry| {
+        let carry = if carry == 1 { 1 << (n - 1) } else { 0 };
+        (0..4).map(move |ab: u64| (((ab & 1) << n) | carry, ((ab >> 1) << n) | carry))
+    })
+}
+
+/// Dense version of the machine, where swapping two outputs is cheap
+struct Circuit {
+    bits: usize,
+    /// Wire names, indexed by wire. The first wires are the X then Y inputs.
+    names: Vec<Wire>,
+    /// Operator and input wires of each gate
+    gates: Vec<(Operator, usize, usize)>,
+    /// Output wire of each gate
+    outputs: Vec<usize>,
+    /// Gate driving each wire
+    drivers: Vec<Option<usize>>,
+    /// Gates using each wire
+    consumers: Vec<Vec<usize>>,
+    /// Z wires
+    z: Vec<usize>,
+}
+
+impl Circuit {
+    fn new(machine: &Machine) -> Result<Self> {
+        let bits = machine.i_bits as usize;
+        let mut names = (0..bits as u16)
+            .map(Wire::get_x)
+            .chain((0..bits as u16).map(Wire::get_y))
+            .collect::<Vec<_>>();
+        let mut ids = names
+            .iter()
+            .enumerate()
+            .map(|(idx, wire)| (*wire, idx))
+            .collect::<FxHashMap<_, _>>();
+        let mut id = |wire: Wire| {
+            *ids.entry(wire).or_insert_with(|| {
+                names.push(wire);
+                names.len() - 1
+            })
+        };
+
+        let mut gates = Vec::with_capacity(machine.gates.len());
+        let mut outputs = Vec::with_capacity(machine.gates.len());
+        for gate in &machine.gates {
+            gates.push((gate.operator, id(gate.in1), id(gate.in2)));
+            outputs.push(id(gate.out));
+        }
+        let z = (0..=bits as u16)
+            .map(|n| id(Wire::get_z(n)))
+            .collect::<Vec<_>>();
+
+        let mut drivers = vec![None; names.len()];
+        let mut consumers = vec![vec![]; names.len()];
+        for (idx, (&(_, in1, in2), &out)) in gates.iter().zip(&outputs).enumerate() {
+            if drivers[out].replace(idx).is_some() {
+                return Err(miette!("wire {} has several drivers", names[out]));
+            }
+            consumers[in1].push(idx);
+            consumers[in2].push(idx);
+        }
+
+        Ok(Self {
+            bits,
+            names,
+            gates,
+            outputs,
+            drivers,
+            consumers,
+            z,
+        })
+    }
+
+    /// Name of the output of a gate
+    fn wire(&self, gate: usize) -> Wire {
+        self.names[self.outputs[gate]]
+    }
+
+    fn swap(&mut self, g1: usize, g2: usize) {
+        self.outputs.swap(g1, g2);
+        self.drivers[self.outputs[g1]] = Some(g1);
+        self.drivers[self.outputs[g2]] = Some(g2);
+    }
+
+    /// The `count` lowest bits of `x + y`, or `None` if a Z wire can't be
+    /// computed (loop or missing driver)
+    fn add(&self, x: u64, y: u64, count: usize) -> Option<u64> {
+        // 0: unknown, 1: being computed, 2: false, 3: true
+        let mut values = vec![0_u8; self.names.len()];
+        for n in 0..self.bits {
+            values[n] = 2 + (x >> n & 1) as u8;
+            values[self.bits + n] = 2 + (y >> n & 1) as u8;
+        }
+        self.z[..count].iter().rev().try_fold(0, |acc, &wire| {
+            Some(acc << 1 | self.value(wire, &mut values)? as u64)
+        })
+    }
+
+    fn value(&self, wire: usize, values: &mut [u8]) -> Option<bool> {
+        match values[wire] {
+            0 => {}
+            1 => return None, // loop
+            v => return Some(v == 3),
+        }
+        values[wire] = 1;
+        let (operator, in1, in2) = self.gates[self.drivers[wire]?];
+        let (in1, in2) = (self.value(in1, values)?, self.value(in2, values)?);
+        let value = match operator {
+            Operator::And => in1 & in2,
+            Operator::Or => in1 | in2,
+            Operator::Xor => in1 ^ in2,
+            Operator::Not => !in1,
+        };
+        values[wire] = 2 + value as u8;
+        Some(value)
+    }
+
+    /// Whether bit `n` (and the lower ones) and its carry out are correct,
+    /// for all the inputs and carries
+    fn bit_ok(&self, n: usize) -> bool {
+        let count = n + 2;
+        let mask = mask(count);
+        bit_checks(n).all(|(x, y)| self.add(x, y, count) == Some(x.wrapping_add(y) & mask))
+    }
+
+    /// Gates likely to be involved in an error on bit `n`: the ones close to
+    /// the X/Y inputs, or to the Z output, of that bit
+    fn suspects(&self, n: usize) -> Vec<usize> {
+        let mut suspects = FxHashSet::default();
+
+        let mut wires = vec![n, self.bits + n];
+        for _ in 0..3 {
+            let gates = wires
+                .iter()
+                .flat_map(|&wire| self.consumers[wire].iter().copied())
+                .collect::<Vec<_>>();
+            wires = gates.iter().map(|&gate| self.outputs[gate]).collect();
+            suspects.extend(gates);
+        }
+
+        for z in [self.z[n], self.z[n + 1]] {
+            let mut wires = vec![z];
+            for _ in 0..3 {
+                let gates = wires
+                    .iter()
+                    .filter_map(|&wire| self.drivers[wire])
+                    .collect::<Vec<_>>();
+                wires = gates
+                    .iter()
+                    .flat_map(|&gate| [self.gates[gate].1, self.gates[gate].2])
+                    .collect();
+                suspects.extend(gates);
+            }
+        }
+
+        suspects.into_iter().sorted().collect()
+    }
+
+    /// Fix the bits from `from` onwards, with the swaps added to `swaps`
+    fn search(&mut self, from: usize, swaps: &mut Vec<(usize, usize)>, max_swaps: usize) -> bool {
+        let Some(n) = (from..self.bits).find(|&n| !self.bit_ok(n)) else {
+            return true;
+        };
+        if swaps.len() == max_swaps {
+            return false;
+        }
+
+        let mut tried = FxHashSet::default();
+        for g1 in self.suspects(n) {
+            for g2 in 0..self.gates.len() {
+                if g1 == g2 || !tried.insert((g1.min(g2), g1.max(g2))) {
+                    continue;
+                }
+                self.swap(g1, g2);
+                if self.bit_ok(n) && (0..n).all(|lower| self.bit_ok(lower)) {
+                    swaps.push((g1, g2));
+                    if self.search(n + 1, swaps, max_swaps) {
+                        return true;
+                    }
+                    swaps.pop();
+                }
+                self.swap(g1, g2);
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse::parse, test_util::adder};
+
+    use super::*;
+
+    #[test]
+    fn correct_adder() {
+        for alternate in [false, true] {
+            let machine = parse(&adder(16, alternate, &[])).unwrap();
+            verify(&machine, RANDOM_CHECKS).unwrap();
+            let repair = repair(&machine, 4).unwrap();
+            assert!(repair.swaps.is_empty());
+        }
+    }
+
+    #[test]
+    fn swapped() {
+        let swaps = [
+            ("z05", "b05"),
+            ("s10", "a10"),
+            ("z15", "c15"),
+            ("b20", "c22"),
+        ];
+        for alternate in [false, true] {
+            let machine = parse(&adder(24, alternate, &swaps)).unwrap();
+            assert!(verify(&machine, RANDOM_CHECKS).is_err());
+
+            let repair = repair(&machine, 4).unwrap();
+            assert_eq!(repair.answer(), "a10,b05,b20,c15,c22,s10,z05,z15");
+            verify(&repair.machine, RANDOM_CHECKS).unwrap();
+        }
+    }
+
+    #[test]
+    fn not_enough_swaps() {
+        let swaps = [("z05", "b05"), ("s10", "a10")];
+        let machine = parse(&adder(20, false, &swaps)).unwrap();
+        assert!(repair(&machine, 1).is_err());
+        assert_eq!(repair(&machine, 2).unwrap().swaps.len(), 2);
+    }
+
+    #[test]
+    fn verify_loop_and_wide() {
+        // The gate driving `s05` then uses `s05` as an input
+        let machine = parse(&adder(8, false, &[("s05", "b05")])).unwrap();
+        assert!(verify(&machine, RANDOM_CHECKS).is_err());
+
+        // The carry out doesn't fit in the output
+        let mut machine = parse(&adder(64, false, &[])).unwrap();
+        assert_eq!(machine.checked_add(u64::MAX, 3), Some((u64::MAX, 3, 2)));
+        assert_eq!(machine.output_bit(64), Some(true));
+        assert_eq!(
+            machine.checked_add(u64::MAX, 0),
+            Some((u64::MAX, 0, u64::MAX))
+        );
+        assert_eq!(machine.output_bit(64), Some(false));
+    }
+
+    #[test]
+    #[ignore = "slow (~15s in debug mode)"]
+    fn verify_64_bits() {
+        let machine = parse(&adder(64, false, &[])).unwrap();
+        verify(&machine, RANDOM_CHECKS).unwrap();
+
+        let machine = parse(&adder(64, false, &[("z64", "c62")])).unwrap();
+        assert!(verify(&machine, RANDOM_CHECKS).is_err());
+    }
+}
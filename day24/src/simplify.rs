@@ -0,0 +1,388 @@
+//! Simplification passes over the gates of a [`Machine`], to reduce large
+//! generated circuits before analysing them.
+//!
+//! The gates are visited once in topological order, each one folded into a
+//! constant, another wire, or a (possibly simpler) gate:
+//! - constant propagation: the initial values of the wires other than the
+//!   X/Y inputs, and of the `fixed` inputs, are constants,
+//! - algebraic identities: `a AND 1 = a`, `a XOR a = 0`, `NOT NOT a = a`,
+//!   `a XOR 1 = NOT a`, ...
+//! - common subexpressions: gates with the same operator and inputs (in any
+//!   order) are merged.
+//!
+//! Z outputs folded into a constant become initial values, the ones folded
+//! into another wire take the name of that wire when it is an internal gate
+//! output, or are driven by a buffer (`a OR a`) otherwise. Finally, the gates
+//! not reaching any Z output are removed.
+
+use std::fmt::Display;
+
+use itertools::Itertools;
+use miette::Result;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::machine::{Gate, Machine, Operator, Wire};
+
+/// What the simplification removed or added, as gate outputs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub gates_before: usize,
+    pub gates_after: usize,
+    /// Gates replaced by a constant
+    pub constants: Vec<Wire>,
+    /// Gates replaced by one of their inputs
+    pub aliases: Vec<Wire>,
+    /// Gates merged with an identical one
+    pub merged: Vec<Wire>,
+    /// Gates not reaching any Z output
+    pub dead: Vec<Wire>,
+    /// Z outputs driven by a new buffer gate
+    pub buffers: Vec<Wire>,
+    /// Constant wires not used anymore
+    pub removed_init: Vec<Wire>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} gates -> {} gates",
+            self.gates_before, self.gates_after
+        )?;
+        for (name, wires) in [
+            ("constants", &self.constants),
+            ("aliases", &self.aliases),
+            ("merged", &self.merged),
+            ("dead", &self.dead),
+            ("buffers", &self.buffers),
+            ("removed initial values", &self.removed_init),
+        ] {
+            if !wires.is_empty() {
+                writeln!(f, "{name} ({}): {}", wires.len(), wires.iter().join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Const(bool),
+    Wire(Wire),
+}
+
+/// Result of folding a gate
+enum Folded {
+    Signal(Signal),
+    Gate(Operator, Wire, Wire),
+}
+
+/// Simplify the machine, with the initial values of the `fixed` inputs also
+/// considered as constants.
+///
+/// Fails if the machine can't be [compiled](Machine::compile).
+pub fn simplify(machine: &Machine, fixed: &[Wire]) -> Result<(Machine, Report)> {
+    let compiled = machine.compile()?;
+    let mut report = Report {
+        gates_before: machine.gates.len(),
+        ..Default::default()
+    };
+
+    let is_input = |wire: &Wire| matches!(wire.io_bit(), Some((b'x' | b'y', _)));
+    let mut signals = machine
+        .init
+        .iter()
+        .filter(|(wire, _)| !is_input(wire) || fixed.contains(wire))
+        .map(|(&wire, &value)| (wire, Signal::Const(value)))
+        .collect::<FxHashMap<_, _>>();
+    // Input of each NOT gate
+    let mut nots = FxHashMap::default();
+    let mut existing = FxHashMap::default();
+    let mut gates = Vec::<Gate>::new();
+
+    for (operator, in1, in2, out) in compiled.gates() {
+        let signal = |wire| *signals.get(&wire).unwrap_or(&Signal::Wire(wire));
+        let not = |wire| match nots.get(&wire) {
+            Some(&input) => Folded::Signal(Signal::Wire(input)),
+            None => Folded::Gate(Operator::Not, wire, wire),
+        };
+        let folded = match (operator, signal(in1), signal(in2)) {
+            (Operator::Not, Signal::Const(a), _) => Folded::Signal(Signal::Const(!a)),
+            (Operator::Not, Signal::Wire(a), _) => not(a),
+            (operator, Signal::Const(a), Signal::Const(b)) => {
+                Folded::Signal(Signal::Const(match operator {
+                    Operator::And => a & b,
+                    Operator::Or => a | b,
+                    _ => a ^ b,
+                }))
+            }
+            (operator, Signal::Const(c), Signal::Wire(w))
+            | (operator, Signal::Wire(w), Signal::Const(c)) => match (operator, c) {
+                (Operator::And, false) => Folded::Signal(Signal::Const(false)),
+                (Operator::Or, true) => Folded::Signal(Signal::Const(true)),
+                (Operator::Xor, true) => not(w),
+                _ => Folded::Signal(Signal::Wire(w)),
+            },
+            (operator, Signal::Wire(a), Signal::Wire(b)) if a == b => match operator {
+                Operator::Xor => Folded::Signal(Signal::Const(false)),
+                _ => Folded::Signal(Signal::Wire(a)),
+            },
+            (operator, Signal::Wire(a), Signal::Wire(b)) => {
+                Folded::Gate(operator, a.min(b), a.max(b))
+            }
+        };
+
+        match folded {
+            Folded::Signal(signal) => {
+                match signal {
+                    Signal::Const(_) => report.constants.push(out),
+                    Signal::Wire(_) => report.aliases.push(out),
+                }
+                signals.insert(out, signal);
+            }
+            Folded::Gate(operator, in1, in2) => match existing.get(&(operator, in1, in2)) {
+                Some(&same) => {
+                    report.merged.push(out);
+                    signals.insert(out, Signal::Wire(same));
+                }
+                None => {
+                    existing.insert((operator, in1, in2), out);
+                    if operator == Operator::Not {
+                        nots.insert(out, in1);
+                    }
+                    gates.push(Gate {
+                        operator,
+                        in1,
+                        in2,
+                        out,
+                        last_val: None,
+                    });
+                }
+            },
+        }
+    }
+
+    // Z outputs that are not driven anymore
+    let mut init = machine
+        .init
+        .iter()
+        .filter(|(wire, _)| is_input(wire))
+        .map(|(&wire, &value)| (wire, value))
+        .collect::<FxHashMap<_, _>>();
+    let mut renamed = FxHashMap::default();
+    for n in 0..machine.o_bits {
+        let z = Wire::get_z(n);
+        match signals.get(&z) {
+            None => {}
+            Some(&Signal::Const(value)) => {
+                init.insert(z, value);
+            }
+            Some(&Signal::Wire(wire)) => {
+                let driven = wire.io_bit().is_none() && gates.iter().any(|gate| gate.out == wire);
+                match renamed.get(&wire) {
+                    None if driven => {
+                        for gate in &mut gates {
+                            for w in [&mut gate.in1, &mut gate.in2, &mut gate.out] {
+                                if *w == wire {
+                                    *w = z;
+                                }
+                            }
+                        }
+                        renamed.insert(wire, z);
+                    }
+                    other => {
+                        let input = *other.unwrap_or(&wire);
+                        report.buffers.push(z);
+                        gates.push(Gate {
+                            operator: Operator::Or,
+                            in1: input,
+                            in2: input,
+                            out: z,
+                            last_val: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Dead gates
+    let drivers = gates
+        .iter()
+        .enumerate()
+        .map(|(idx, gate)| (gate.out, idx))
+        .collect::<FxHashMap<_, _>>();
+    let mut live = FxHashSet::default();
+    let mut stack = (0..machine.o_bits)
+        .filter_map(|n| drivers.get(&Wire::get_z(n)).copied())
+        .collect::<Vec<_>>();
+    while let Some(idx) = stack.pop() {
+        if live.insert(idx) {
+            let gate = &gates[idx];
+            stack.extend([gate.in1, gate.in2].iter().filter_map(|w| drivers.get(w)));
+        }
+    }
+    let (gates, dead): (Vec<_>, Vec<_>) = gates
+        .into_iter()
+        .enumerate()
+        .partition(|(idx, _)| live.contains(idx));
+    report.dead = dead.into_iter().map(|(_, gate)| gate.out).collect();
+    let gates = gates.into_iter().map(|(_, gate)| gate).collect::<Vec<_>>();
+
+    report.removed_init = machine
+        .init
+        .keys()
+        .filter(|wire| !init.contains_key(wire))
+        .copied()
+        .collect();
+    report.gates_after = gates.len();
+    for wires in [
+        &mut report.constants,
+        &mut report.aliases,
+        &mut report.merged,
+        &mut report.dead,
+        &mut report.buffers,
+        &mut report.removed_init,
+    ] {
+        wires.sort();
+    }
+
+    Ok((Machine::new(init, gates), report))
+}
+
+impl Machine {
+    /// See [`simplify`]
+    pub fn simplify(&self, fixed: &[Wire]) -> Result<(Machine, Report)> {
+        simplify(self, fixed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{bdd::Reference, parse::parse, test_util::adder};
+
+    use super::*;
+
+    // cspell:disable
+    const INPUT_SAMPLE: &[u8] = br#"
+one: 1
+x00: 1
+x01: 0
+y00: 0
+y01: 1
+nil: 0
+
+x00 AND one -> a00
+a00 XOR y00 -> z00
+x01 OR nil -> b01
+y01 XOR one -> n01
+NOT n01 -> m01
+b01 AND m01 -> z01
+y01 AND x01 -> z02
+nil AND x00 -> z03
+x00 OR y00 -> ddd
+b01 XOR b01 -> eee
+eee OR x01 -> z04
+"#;
+    // cspell:enable
+
+    fn wires(names: &str) -> Vec<Wire> {
+        names.split(',').map(|name| name.parse().unwrap()).collect()
+    }
+
+    /// All the outputs of the machines are the same
+    fn assert_same(m1: &Machine, m2: &Machine) {
+        let bits = m1.i_bits;
+        let (mut m1, mut m2) = (m1.clone(), m2.clone());
+        for x in 0..1 << bits {
+            for y in 0..1 << bits {
+                assert_eq!(m1.add(x, y), m2.add(x, y), "{x} + {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn sample() {
+        let machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        let (simplified, report) = simplify(&machine, &[]).unwrap();
+        assert_eq!(
+            simplified.to_string(),
+            r#"x00: 1
+x01: 0
+y00: 0
+y01: 1
+z03: 0
+
+x00 XOR y00 -> z00
+x01 AND y01 -> z01
+z01 OR z01 -> z02
+x01 OR x01 -> z04
+"#
+        );
+        assert_eq!(
+            report,
+            Report {
+                gates_before: 11,
+                gates_after: 4,
+                constants: wires("eee,z03"),
+                aliases: wires("a00,b01,m01,z04"),
+                merged: wires("z02"),
+                dead: wires("ddd,n01"),
+                buffers: wires("z02,z04"),
+                removed_init: wires("nil,one"),
+            }
+        );
+        assert_same(&machine, &simplified);
+
+        // Simplifying again only re-creates the buffers
+        let (again, report) = simplify(&simplified, &[]).unwrap();
+        assert_eq!(again.to_string(), simplified.to_string());
+        assert_eq!(report.gates_before, report.gates_after);
+    }
+
+    #[test]
+    fn fixed() {
+        let machine = parse(&INPUT_SAMPLE[1..]).unwrap();
+        let (simplified, report) = simplify(&machine, &wires("x00,x01")).unwrap();
+        assert_eq!(report.gates_after, 1);
+        // x00 is 1 and x01 is 0: z00 = NOT y00, the other bits are 0
+        let mut simplified = simplified;
+        for y in 0..4 {
+            let z = simplified.add(0b01, y).2;
+            assert_eq!(z, machine.clone().add(0b01, y).2);
+            assert_eq!(simplified.add(0b10, y).2, z);
+        }
+    }
+
+    #[test]
+    fn adder_with_redundancy() {
+        let mut text = String::from_utf8(adder(16, false, &[])).unwrap();
+        // Duplicate gates, double negations, and unused gates
+        text += "y03 XOR x03 -> d03\n";
+        text += "d03 XOR c02 -> e03\n";
+        text += "NOT x05 -> n05\n";
+        text += "NOT n05 -> m05\n";
+        text += "m05 AND y05 -> f05\n";
+        text += "f05 OR e03 -> g05\n";
+        let machine = parse(text.as_bytes()).unwrap();
+        let gates = machine.gates.len();
+
+        let (simplified, report) = simplify(&machine, &[]).unwrap();
+        assert_eq!(report.merged, wires("d03,e03,f05"));
+        assert_eq!(report.aliases, wires("m05"));
+        assert_eq!(report.dead, wires("g05,n05"));
+        assert_eq!(simplified.gates.len(), gates - 6);
+        assert_eq!(simplified.check(Reference::Add).unwrap(), None);
+    }
+
+    #[test]
+    fn not_gate() {
+        let text = b"x00: 1\ny00: 0\n\nNOT x00 -> abc\nabc AND y00 -> z00\n";
+        let mut machine = parse(text).unwrap();
+        assert_eq!(machine.gates[0].operator, Operator::Not);
+        assert_eq!(machine.to_string().as_bytes(), text);
+        assert_eq!(machine.add(0, 1).2, 1);
+        assert_eq!(machine.add(1, 1).2, 0);
+        assert_eq!(machine.compile().unwrap().add(0, 1), 1);
+    }
+}
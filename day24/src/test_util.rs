@@ -0,0 +1,115 @@
+//! Circuit fixtures shared by the tests.
+
+use std::fmt::Write;
+
+use crate::{machine::Machine, parse::parse};
+
+/// Ripple-carry adder in the input format, with some outputs swapped.
+/// The full-adders either combine X and Y first, then the carry
+/// (`alternate == false`), or X and the carry first, then Y.
+pub fn adder(bits: usize, alternate: bool, swaps: &[(&str, &str)]) -> Vec<u8> {
+    let mut text = String::new();
+    for n in 0..bits {
+        text += &format!("x{n:02}: {}\n", n % 2);
+    }
+    for n in 0..bits {
+        text += &format!("y{n:02}: {}\n", n % 3 % 2);
+    }
+    text += "\n";
+
+    let mut lines = vec![
+        "x00 XOR y00 -> z00".to_string(),
+        "x00 AND y00 -> c00".to_string(),
+    ];
+    for n in 1..bits {
+        let (x, y, carry) = (
+            format!("x{n:02}"),
+            format!("y{n:02}"),
+            format!("c{:02}", n - 1),
+        );
+        let (first, second, third) = if alternate {
+            (x, carry, y)
+        } else {
+            (x, y, carry)
+        };
+        let carry_out = if n + 1 == bits {
+            format!("z{bits:02}")
+        } else {
+            format!("c{n:02}")
+        };
+        lines.push(format!("{first} XOR {second} -> s{n:02}"));
+        lines.push(format!("{second} AND {first} -> a{n:02}"));
+        lines.push(format!("s{n:02} XOR {third} -> z{n:02}"));
+        lines.push(format!("{third} AND s{n:02} -> b{n:02}"));
+        lines.push(format!("a{n:02} OR b{n:02} -> {carry_out}"));
+    }
+
+    for line in lines {
+        let (gate, out) = line.split_once(" -> ").unwrap();
+        let out = swaps
+            .iter()
+            .find_map(|&(a, b)| match out {
+                _ if out == a => Some(b),
+                _ if out == b => Some(a),
+                _ => None,
+            })
+            .unwrap_or(out);
+        text += &format!("{gate} -> {out}\n");
+    }
+    text.into_bytes()
+}
+
+/// Machine text with `bits` bit inputs, and gates written by `gates`
+pub fn machine(bits: usize, gates: impl FnOnce(&mut Builder)) -> Machine {
+    let mut builder = Builder {
+        text: String::new(),
+        next: 100,
+    };
+    for letter in ['x', 'y'] {
+        for n in 0..bits {
+            writeln!(builder.text, "{letter}{n:02}: 0").unwrap();
+        }
+    }
+    writeln!(builder.text).unwrap();
+    gates(&mut builder);
+    parse(builder.text.as_bytes()).unwrap()
+}
+
+pub struct Builder {
+    pub text: String,
+    next: usize,
+}
+
+impl Builder {
+    pub fn gate(&mut self, in1: &str, operator: &str, in2: &str) -> String {
+        self.next += 1;
+        let out = self.next.to_string();
+        writeln!(self.text, "{in1} {operator} {in2} -> {out}").unwrap();
+        out
+    }
+
+    /// Sum and carry of the bits
+    pub fn add(&mut self, bits: &[String]) -> (Option<String>, Option<String>) {
+        match bits {
+            [] => (None, None),
+            [a] => (Some(a.clone()), None),
+            [a, b] => (Some(self.gate(a, "XOR", b)), Some(self.gate(a, "AND", b))),
+            [a, b, c] => {
+                let half = self.gate(a, "XOR", b);
+                let sum = self.gate(&half, "XOR", c);
+                let generate = self.gate(a, "AND", b);
+                let propagate = self.gate(&half, "AND", c);
+                (Some(sum), Some(self.gate(&generate, "OR", &propagate)))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn output(&mut self, n: usize, wire: Option<String>) {
+        match wire {
+            Some(wire) => writeln!(self.text, "{wire} OR {wire} -> z{n:02}"),
+            None => writeln!(self.text, "x00 XOR x00 -> z{n:02}"),
+        }
+        .unwrap();
+    }
+}